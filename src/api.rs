@@ -1,55 +1,215 @@
-use actix_web::web::Json;
+use actix_web::web::{self, Data, Json};
 use actix_web::{Responder, get, post};
 use chrono::{DateTime, Utc};
 use chrono_tz::America::Sao_Paulo;
 use chrono_tz::{CET, Tz};
 
-use crate::command::{convert_from_input_or_default_timezones, process_input};
-use crate::telegram::{InlineQueryResult, RequestType, TelegramRequest, TelegramResponse};
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::command::{
+    Context, Reply, ReplyOption, convert_from_input_or_default_timezones, disambiguations,
+    process_input,
+};
+use crate::storage::Storage;
+use crate::telegram::{
+    BotApi, InlineKeyboardButton, InlineKeyboardMarkup, InlineQueryResult, MAX_CALLBACK_DATA_LEN,
+    Message, RequestType, TelegramRequest, TelegramResponse, User,
+};
+use crate::zone::Zone;
 
 const DEFAULT_INLINE_TIMEZONES: [Tz; 2] = [CET, Sao_Paulo];
 
+/// Who the bot is on Telegram. Without a username, commands addressed to
+/// any bot are taken as ours.
+#[derive(Debug, Default)]
+pub struct BotInfo {
+    pub username: Option<String>,
+    /// Used to send replies directly and learn their ids. Without it,
+    /// replies answer the webhook and edits guess which reply to update.
+    pub api: Option<BotApi>,
+}
+
 #[get("/")]
 pub async fn welcome() -> impl Responder {
     "<h1>Welcome!</h1>"
 }
 
 #[post("/")]
-pub async fn receive_message(Json(payload): Json<TelegramRequest>) -> impl Responder {
-    Json(handle_update(payload, Utc::now()))
+pub async fn receive_message(
+    Json(payload): Json<TelegramRequest>,
+    storage: Data<dyn Storage>,
+    bot: Data<BotInfo>,
+) -> impl Responder {
+    let source = payload
+        .message
+        .as_ref()
+        .or(payload.edited_message.as_ref())
+        .map(|message| (message.chat.id, message.message_id));
+    let callback_id = payload
+        .callback_query
+        .as_ref()
+        .map(|callback| callback.id.clone());
+    // Commands may save settings, which writes the store to disk.
+    let response = web::block({
+        let (storage, bot) = (storage.clone(), bot.clone());
+        move || handle_update(payload, storage.as_ref(), &bot, Utc::now())
+    })
+    .await
+    .ok()
+    .flatten();
+    if let Some(callback_id) = callback_id {
+        answer_callback_query(callback_id, &bot).await;
+    }
+    Json(deliver(response, source, storage.as_ref(), &bot).await)
+}
+
+/// Stops the spinner on a pressed button. The webhook answer is taken by
+/// the edit the button causes, so this needs the Bot API.
+async fn answer_callback_query(callback_id: String, bot: &BotInfo) {
+    let Some(api) = bot.api.clone() else {
+        return;
+    };
+    let answer = json!({ "callback_query_id": callback_id });
+    // Telegram gives up on unanswered buttons by itself, so errors can wait.
+    web::block(move || api.call::<bool>("answerCallbackQuery", &answer))
+        .await
+        .ok();
+}
+
+/// Sends a reply to `source` through the Bot API when possible, and
+/// remembers its id for when `source` is edited. Returns the response left
+/// for the webhook, which is the reply itself if Telegram did not take it.
+async fn deliver(
+    response: Option<TelegramResponse>,
+    source: Option<(i64, i64)>,
+    storage: &dyn Storage,
+    bot: &BotInfo,
+) -> Option<TelegramResponse> {
+    match (response, source, &bot.api) {
+        (
+            Some(response @ TelegramResponse::SendMessage { .. }),
+            Some((chat_id, message_id)),
+            Some(api),
+        ) => {
+            let api = api.clone();
+            let (response, sent) = web::block(move || {
+                let sent = api.send(&response);
+                (response, sent)
+            })
+            .await
+            .ok()?;
+            match sent {
+                Ok(Some(reply_id)) => {
+                    // The reply is out already; losing its id only means a
+                    // later edit gets a new reply.
+                    storage.set_reply_id(chat_id, message_id, reply_id).ok();
+                    None
+                }
+                // Sent, so answering the webhook too would send it twice.
+                Ok(None) => None,
+                Err(_) => Some(response),
+            }
+        }
+        (response, ..) => response,
+    }
 }
 
-pub fn handle_update(payload: TelegramRequest, now: DateTime<Utc>) -> Option<TelegramResponse> {
+pub fn handle_update(
+    payload: TelegramRequest,
+    storage: &dyn Storage,
+    bot: &BotInfo,
+    now: DateTime<Utc>,
+) -> Option<TelegramResponse> {
+    let context = |user: &User, chat_id| Context {
+        storage,
+        user_id: user.id,
+        user_name: user.display_name(),
+        language_code: user.language_code.clone(),
+        chat_id,
+        now,
+    };
     match RequestType::from_request(payload) {
         RequestType::Message(message) => {
             if message.is_from_bot() {
                 return None;
             }
-            let text = message.text?;
+            let text = input_text(&message, bot)?;
+            let reply = process_input(&text, &context(&message.from, message.chat.id));
+            // Scanned chat messages without any time get no reply.
+            if reply.text.is_empty() {
+                return None;
+            }
+            let (text, reply_markup) = reply_parts(reply);
             Some(TelegramResponse::SendMessage {
                 chat_id: message.chat.id,
-                text: process_input(&text, now),
+                text,
+                reply_markup,
             })
         }
 
         RequestType::EditedMessage(message) => {
-            let text = message.text?;
-            Some(TelegramResponse::EditMessageText {
-                chat_id: message.chat.id,
-                message_id: message.message_id + 1,
-                text: process_input(&text, now),
-            })
+            let text = input_text(&message, bot)?;
+            let reply = process_input(&text, &context(&message.from, message.chat.id));
+            if reply.text.is_empty() {
+                return None;
+            }
+            let (text, reply_markup) = reply_parts(reply);
+            let reply_id = match storage.reply_id(message.chat.id, message.message_id) {
+                Some(reply_id) => Some(reply_id),
+                // Webhook answers leave no id behind; the reply most likely
+                // came right after the message.
+                None if bot.api.is_none() => Some(message.message_id + 1),
+                None => None,
+            };
+            match reply_id {
+                Some(reply_id) => Some(TelegramResponse::EditMessageText {
+                    chat_id: message.chat.id,
+                    message_id: reply_id,
+                    text,
+                    reply_markup,
+                }),
+                // The first reply's id is unknown, so answer again instead.
+                None => Some(TelegramResponse::SendMessage {
+                    chat_id: message.chat.id,
+                    text,
+                    reply_markup,
+                }),
+            }
         }
 
         RequestType::InlineQuery(inline) => {
-            let converter = convert_from_input_or_default_timezones(
-                inline.query.trim(),
-                &DEFAULT_INLINE_TIMEZONES,
-            )
-            .ok()?;
-            let results = converter
-                .convert_time_between_timezones(now)
-                .ok()?
+            let query = inline.query.trim();
+            // Inline queries carry no chat, so only the sender's own zones apply.
+            let ctx = context(&inline.from, inline.from.id);
+            let mut default_zones = ctx.user_zones();
+            if default_zones.is_empty() {
+                default_zones = DEFAULT_INLINE_TIMEZONES.map(Zone::from).to_vec();
+            }
+            let converters = match convert_from_input_or_default_timezones(query, &default_zones) {
+                Ok(converter) => vec![converter],
+                Err(error) => disambiguations(query, &error)
+                    .into_iter()
+                    .filter_map(|(_, query)| {
+                        convert_from_input_or_default_timezones(&query, &default_zones).ok()
+                    })
+                    .collect(),
+            };
+            if converters.is_empty() {
+                return None;
+            }
+            let times: Vec<String> = converters
+                .into_iter()
+                .map(|converter| {
+                    converter
+                        .with_format(ctx.time_format())
+                        .with_messages(ctx.language().messages())
+                        .convert_time_between_timezones(now)
+                })
+                .flatten_ok()
+                .try_collect()
+                .ok()?;
+            let results = times
                 .into_iter()
                 .enumerate()
                 .map(|(idx, time)| InlineQueryResult::article(idx.to_string(), time))
@@ -60,20 +220,81 @@ pub fn handle_update(payload: TelegramRequest, now: DateTime<Utc>) -> Option<Tel
             })
         }
 
+        RequestType::CallbackQuery(callback) => {
+            let message = callback.message?;
+            let reply = process_input(&callback.data?, &context(&callback.from, message.chat.id));
+            let (text, reply_markup) = reply_parts(reply);
+            Some(TelegramResponse::EditMessageText {
+                chat_id: message.chat.id,
+                message_id: message.message_id,
+                text,
+                reply_markup,
+            })
+        }
+
         RequestType::Unknown => None,
     }
 }
 
+/// The message text to process, with "@username" taken off a command
+/// addressed to this bot. Commands addressed to other bots give None.
+fn input_text(message: &Message, bot: &BotInfo) -> Option<String> {
+    let Some(command) = message.bot_command() else {
+        return message.text.clone();
+    };
+    if let (Some(target), Some(username)) = (command.bot, &bot.username)
+        && !target.eq_ignore_ascii_case(username)
+    {
+        return None;
+    }
+    if command.args.is_empty() {
+        Some(command.name.to_string())
+    } else {
+        Some(format!("{} {}", command.name, command.args))
+    }
+}
+
+/// The reply's text and keyboard. Options whose input is too long for a
+/// button are listed in the text instead, so a question is never left
+/// without answers.
+fn reply_parts(reply: Reply) -> (String, Option<InlineKeyboardMarkup>) {
+    let (buttons, listed): (Vec<ReplyOption>, Vec<ReplyOption>) = reply
+        .options
+        .into_iter()
+        .partition(|option| option.input.len() <= MAX_CALLBACK_DATA_LEN);
+    let mut text = reply.text;
+    if !listed.is_empty() {
+        text += "\n\n";
+        text += &listed.iter().map(|option| &option.label).join("\n");
+    }
+    let buttons = buttons.into_iter().map(|option| InlineKeyboardButton {
+        text: option.label,
+        callback_data: option.input,
+    });
+    (text, InlineKeyboardMarkup::single_column(buttons))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Only the module: the `test` attribute macro would shadow the built-in
+    // one used by synchronous tests.
     use actix_web::{
         App,
         http::{Method, header::ContentType},
-        test,
+        test::{self},
     };
-    use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+
+    use crate::storage::JsonStorage;
+
+    fn in_memory_storage() -> Data<dyn Storage> {
+        Data::from(Arc::new(JsonStorage::in_memory()) as Arc<dyn Storage>)
+    }
 
     #[actix_web::test]
     async fn test_welcome() {
@@ -86,7 +307,13 @@ mod tests {
 
     #[actix_web::test]
     async fn test_receive_message() {
-        let app = test::init_service(App::new().service(receive_message)).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(in_memory_storage())
+                .app_data(Data::new(BotInfo::default()))
+                .service(receive_message),
+        )
+        .await;
         let req = test::TestRequest::default()
             .method(Method::POST)
             .insert_header(ContentType::json())
@@ -114,19 +341,25 @@ mod tests {
         assert!(resp.status().is_success());
 
         let data: TelegramResponse = test::read_body_json(resp).await;
-        let TelegramResponse::SendMessage { chat_id, text } = data else {
+        let TelegramResponse::SendMessage { chat_id, text, .. } = data else {
             panic!("expected sendMessage response, got {data:?}");
         };
         assert_eq!(chat_id, 123);
         assert_eq!(
             text,
-            "Welcome!\n\nCommands accepted:\n/start\n/now <timezone>\n/convert <time> <source_timezone> <target_timezone>"
+            "Welcome!\n\nCommands accepted:\n/start\n/now <timezone>\n/convert [date] <time> <source_timezone> <target_timezone>\n/settz <home_timezone> [favourite_timezones]\n/addtz <timezones>\n/removetz <timezones>\n/listtz\n/who\n/meet [date] [working_hours] <timezones>\n/tzinfo <timezones>\n/diff [date] <timezone> <timezones>\n/until [date] <time> <timezone>\n/since [date] <time> <timezone>\n/epoch [date] [time] [timezone]\n/scan [on|off]\n/lang [en|pt|es|auto]\n/format [chat] [12h|24h] [seconds] [offset] [weekday] [date] | [chat] reset"
         );
     }
 
     #[actix_web::test]
     async fn test_receive_inline_message() {
-        let app = test::init_service(App::new().service(receive_message)).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(in_memory_storage())
+                .app_data(Data::new(BotInfo::default()))
+                .service(receive_message),
+        )
+        .await;
         let req = test::TestRequest::default()
             .method(Method::POST)
             .insert_header(ContentType::json())
@@ -155,4 +388,299 @@ mod tests {
         };
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_handle_update_ambiguous_offers_keyboard() {
+        let payload = serde_json::from_value(json!(
+            {
+                "update_id": 1,
+                "message": {
+                    "message_id": 10,
+                    "text": "12:00 IST BRT",
+                    "date": 123,
+                    "from": {"id": 1, "is_bot": false, "first_name": "John"},
+                    "chat": {"id": 5, "type": "private"},
+                }
+            }
+        ))
+        .unwrap();
+        let Some(TelegramResponse::SendMessage { reply_markup, .. }) = handle_update(
+            payload,
+            &JsonStorage::in_memory(),
+            &BotInfo::default(),
+            Utc::now(),
+        ) else {
+            panic!("expected sendMessage response");
+        };
+        let keyboard = reply_markup.expect("expected a keyboard");
+        assert_eq!(keyboard.inline_keyboard.len(), 3);
+        assert_eq!(
+            keyboard.inline_keyboard[1][0].callback_data,
+            "12:00 Asia/Kolkata BRT"
+        );
+    }
+
+    #[test]
+    fn test_handle_update_callback_query_edits_message() {
+        let payload = serde_json::from_value(json!(
+            {
+                "update_id": 2,
+                "callback_query": {
+                    "id": "abc",
+                    "from": {"id": 1, "is_bot": false, "first_name": "John"},
+                    "message": {"message_id": 11, "chat": {"id": 5, "type": "private"}},
+                    "data": "12:00 Asia/Kolkata UTC",
+                }
+            }
+        ))
+        .unwrap();
+        let Some(TelegramResponse::EditMessageText {
+            chat_id,
+            message_id,
+            text,
+            reply_markup,
+        }) = handle_update(
+            payload,
+            &JsonStorage::in_memory(),
+            &BotInfo::default(),
+            Utc::now(),
+        )
+        else {
+            panic!("expected editMessageText response");
+        };
+        assert_eq!((chat_id, message_id), (5, 11));
+        assert_eq!(text, "12:00 Asia/Kolkata - 06:30 UTC");
+        assert!(reply_markup.is_none());
+    }
+
+    fn group_command(text: &str, length: u32) -> TelegramRequest {
+        serde_json::from_value(json!(
+            {
+                "update_id": 3,
+                "message": {
+                    "message_id": 12,
+                    "text": text,
+                    "date": 123,
+                    "from": {"id": 1, "is_bot": false, "first_name": "John"},
+                    "chat": {"id": -5, "type": "group"},
+                    "entities": [{"offset": 0, "length": length, "type": "bot_command"}],
+                }
+            }
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_handle_update_strips_bot_username() {
+        let bot = BotInfo {
+            username: Some("chronos_bot".into()),
+            ..BotInfo::default()
+        };
+        let payload = group_command("/now@Chronos_Bot utc", 16);
+        let Some(TelegramResponse::SendMessage { text, .. }) =
+            handle_update(payload, &JsonStorage::in_memory(), &bot, Utc::now())
+        else {
+            panic!("expected sendMessage response");
+        };
+        assert!(text.ends_with(" UTC"), "unexpected reply {text:?}");
+
+        let payload = group_command("/now@other_bot utc", 14);
+        assert!(handle_update(payload, &JsonStorage::in_memory(), &bot, Utc::now()).is_none());
+    }
+
+    fn edited_message(message_id: i64) -> TelegramRequest {
+        serde_json::from_value(json!(
+            {
+                "update_id": 4,
+                "edited_message": {
+                    "message_id": message_id,
+                    "text": "12:00 UTC BRT",
+                    "date": 123,
+                    "from": {"id": 1, "is_bot": false, "first_name": "John"},
+                    "chat": {"id": -5, "type": "group"},
+                }
+            }
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_handle_update_edit_updates_known_reply() {
+        let storage = JsonStorage::in_memory();
+        storage.set_reply_id(-5, 12, 15).unwrap();
+        let Some(TelegramResponse::EditMessageText {
+            chat_id,
+            message_id,
+            text,
+            ..
+        }) = handle_update(
+            edited_message(12),
+            &storage,
+            &BotInfo::default(),
+            Utc::now(),
+        )
+        else {
+            panic!("expected editMessageText response");
+        };
+        assert_eq!((chat_id, message_id), (-5, 15));
+        assert_eq!(text, "12:00 UTC - 09:00 BRT");
+    }
+
+    #[test]
+    fn test_handle_update_edit_without_api_guesses_next_message() {
+        let storage = JsonStorage::in_memory();
+        let response = handle_update(
+            edited_message(13),
+            &storage,
+            &BotInfo::default(),
+            Utc::now(),
+        );
+        let Some(TelegramResponse::EditMessageText { message_id, .. }) = response else {
+            panic!("expected editMessageText response, got {response:?}");
+        };
+        assert_eq!(message_id, 14);
+    }
+
+    #[test]
+    fn test_handle_update_edit_without_known_reply_sends_new_one() {
+        let storage = JsonStorage::in_memory();
+        let bot = BotInfo {
+            api: Some(BotApi::new("token".into())),
+            ..BotInfo::default()
+        };
+        let response = handle_update(edited_message(13), &storage, &bot, Utc::now());
+        let Some(TelegramResponse::SendMessage { chat_id, text, .. }) = response else {
+            panic!("expected sendMessage response, got {response:?}");
+        };
+        assert_eq!(chat_id, -5);
+        assert_eq!(text, "12:00 UTC - 09:00 BRT");
+    }
+
+    /// A Bot API stand-in answering a single call with `status` and `body`.
+    /// Joining the thread gives back the request line it received.
+    fn stub_api(status: u16, body: &'static str) -> (BotInfo, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            reader.read_exact(&mut vec![0; length]).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\n\
+                Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request_line
+        });
+        let bot = BotInfo {
+            api: Some(BotApi::with_url(url, "token".into())),
+            ..BotInfo::default()
+        };
+        (bot, handle)
+    }
+
+    fn reply(text: &str) -> Option<TelegramResponse> {
+        Some(TelegramResponse::SendMessage {
+            chat_id: -5,
+            text: text.into(),
+            reply_markup: None,
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_deliver_records_reply_id() {
+        let (bot, api) = stub_api(200, r#"{"ok":true,"result":{"message_id":77}}"#);
+        let storage = JsonStorage::in_memory();
+        let response = deliver(reply("12:00 UTC"), Some((-5, 12)), &storage, &bot).await;
+        assert!(response.is_none());
+        assert!(
+            api.join()
+                .unwrap()
+                .starts_with("POST /bottoken/sendMessage ")
+        );
+        assert_eq!(storage.reply_id(-5, 12), Some(77));
+    }
+
+    #[actix_web::test]
+    async fn test_deliver_does_not_resend_when_result_is_unreadable() {
+        let (bot, api) = stub_api(200, "not json");
+        let storage = JsonStorage::in_memory();
+        let response = deliver(reply("12:00 UTC"), Some((-5, 12)), &storage, &bot).await;
+        api.join().unwrap();
+        assert!(response.is_none());
+        assert_eq!(storage.reply_id(-5, 12), None);
+    }
+
+    #[actix_web::test]
+    async fn test_deliver_falls_back_to_webhook() {
+        let (bot, api) = stub_api(500, r#"{"ok":false}"#);
+        let storage = JsonStorage::in_memory();
+        let response = deliver(reply("12:00 UTC"), Some((-5, 12)), &storage, &bot).await;
+        api.join().unwrap();
+        assert!(matches!(
+            response,
+            Some(TelegramResponse::SendMessage { .. })
+        ));
+        assert_eq!(storage.reply_id(-5, 12), None);
+    }
+
+    #[test]
+    fn test_get_me_reads_username() {
+        let (bot, api) = stub_api(
+            200,
+            r#"{"ok":true,"result":{"id":7,"is_bot":true,"first_name":"Chronos","username":"chronos_bot"}}"#,
+        );
+        let me = bot.api.unwrap().get_me().unwrap().unwrap();
+        assert!(api.join().unwrap().starts_with("POST /bottoken/getMe "));
+        assert_eq!(me.username.as_deref(), Some("chronos_bot"));
+    }
+
+    #[test]
+    fn test_reply_parts_lists_options_too_long_for_buttons() {
+        let option = |label: &str, input: String| ReplyOption {
+            label: label.into(),
+            input,
+        };
+        let reply = Reply {
+            text: "Which one did you mean?".into(),
+            options: vec![
+                option("Asia/Kolkata", "12:00 Asia/Kolkata".into()),
+                option(
+                    "Asia/Jerusalem",
+                    format!("12:00 Asia/Jerusalem {}", "UTC ".repeat(15)),
+                ),
+            ],
+        };
+        let (text, keyboard) = reply_parts(reply);
+        assert_eq!(text, "Which one did you mean?\n\nAsia/Jerusalem");
+        let keyboard = keyboard.expect("expected a keyboard");
+        assert_eq!(keyboard.inline_keyboard.len(), 1);
+        assert_eq!(keyboard.inline_keyboard[0][0].text, "Asia/Kolkata");
+    }
+
+    #[actix_web::test]
+    async fn test_answer_callback_query() {
+        let (bot, api) = stub_api(200, r#"{"ok":true,"result":true}"#);
+        answer_callback_query("abc".into(), &bot).await;
+        assert!(
+            api.join()
+                .unwrap()
+                .starts_with("POST /bottoken/answerCallbackQuery ")
+        );
+    }
 }
@@ -1,81 +1,922 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Offset, TimeDelta, Utc};
 use chrono_tz::Tz;
 use itertools::Itertools;
 
-use crate::converter::Converter;
+use crate::converter::{Converter, take_prefix, timezone_parser};
 use crate::error::BotError;
-use crate::time;
+use crate::gazetteer;
+use crate::i18n::{Language, Messages, fill};
+use crate::meeting::MeetingPlanner;
+use crate::scanner::{find_mentions, mentions_time};
+use crate::storage::{ChatMember, Storage};
+use crate::time::{
+    OffsetChange, TimeFormat, WORKING_HOURS, format_day_shift, format_time_with_timezone,
+    format_timezone, is_dst, is_working_hours, offset_change, parse_date_prefix, parse_zone,
+    time_with_timezone,
+};
+use crate::zone::Zone;
 
-pub fn process_input(text: &str, now: DateTime<Utc>) -> String {
+/// A reply to send back to the user, optionally with choices the user can
+/// pick from. Each choice carries the input to process when picked.
+#[derive(Debug, PartialEq)]
+pub struct Reply {
+    pub text: String,
+    pub options: Vec<ReplyOption>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReplyOption {
+    pub label: String,
+    pub input: String,
+}
+
+impl From<String> for Reply {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            options: Vec::new(),
+        }
+    }
+}
+
+impl PartialEq<String> for Reply {
+    fn eq(&self, other: &String) -> bool {
+        self.options.is_empty() && self.text == *other
+    }
+}
+
+/// Who sent the input being processed, from which chat, and where to keep
+/// their settings.
+pub struct Context<'a> {
+    pub storage: &'a dyn Storage,
+    pub user_id: i64,
+    pub user_name: String,
+    /// The sender's Telegram locale, e.g. "pt-br".
+    pub language_code: Option<String>,
+    pub chat_id: i64,
+    pub now: DateTime<Utc>,
+}
+
+impl Context<'_> {
+    /// The sender's home timezone followed by their favourites. Stored zones
+    /// that no longer parse are skipped.
+    pub fn user_zones(&self) -> Vec<Zone> {
+        let settings = self.storage.user_settings(self.user_id);
+        parse_stored_zones(settings.zones())
+    }
+
+    /// The timezones registered for the current chat.
+    pub fn chat_zones(&self) -> Vec<Zone> {
+        let settings = self.storage.chat_settings(self.chat_id);
+        parse_stored_zones(settings.zones.iter().map(String::as_str))
+    }
+
+    /// Zones to convert into when the input names none: the chat's roster,
+    /// or the sender's own zones when the chat has no roster.
+    pub fn default_zones(&self) -> Vec<Zone> {
+        let zones = self.chat_zones();
+        if zones.is_empty() {
+            self.user_zones()
+        } else {
+            zones
+        }
+    }
+
+    /// The language chosen with /lang, else the Telegram locale, else
+    /// English.
+    pub fn language(&self) -> Language {
+        let settings = self.storage.user_settings(self.user_id);
+        settings
+            .language
+            .as_deref()
+            .or(self.language_code.as_deref())
+            .and_then(Language::from_code)
+            .unwrap_or_default()
+    }
+
+    /// The chat's format chosen with /format chat, else the sender's own,
+    /// else the default 24-hour clock.
+    pub fn time_format(&self) -> TimeFormat {
+        self.storage
+            .chat_settings(self.chat_id)
+            .format
+            .or(self.storage.user_settings(self.user_id).format)
+            .unwrap_or_default()
+    }
+}
+
+fn parse_stored_zones<'a>(zones: impl Iterator<Item = &'a str>) -> Vec<Zone> {
+    zones.filter_map(|zone| parse_zone(zone).ok()).collect()
+}
+
+pub fn process_input(text: &str, ctx: &Context) -> Reply {
     let (command, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let now = ctx.now;
+    let t = ctx.language().messages();
+    let format = ctx.time_format();
+    let reply = |result| reply_or_disambiguate(text, result, t, |e| error_message(&e, t));
+    let pattern_reply =
+        |result, info| reply_or_disambiguate(text, result, t, |e| pattern_error(&e, info, t));
     match command {
-        "/start" => command_start(),
-        "/now" => command_now(rest, now).unwrap_or_else(|e| e.to_string()),
-        "/convert" => command_convert(rest, now).unwrap_or_else(|e| convert_error(&e)),
-        _ => normal_message(text, now),
+        "/start" => command_start(t).into(),
+        "/now" => reply(command_now(rest, now, &format, t)),
+        "/convert" => pattern_reply(
+            command_convert(rest, &ctx.default_zones(), now, &format, t),
+            CONVERT_COMMAND_INFO,
+        ),
+        "/settz" => reply(command_settz(rest, ctx, t)),
+        "/addtz" => reply(command_addtz(rest, ctx, t)),
+        "/removetz" => reply(command_removetz(rest, ctx, t)),
+        "/listtz" => command_listtz(ctx, t).into(),
+        "/who" | "/team" => command_who(ctx, t).into(),
+        "/tzinfo" => reply(command_tzinfo(rest, &ctx.default_zones(), now, t)),
+        "/diff" => reply(command_diff(rest, &ctx.default_zones(), now, t)),
+        "/until" => pattern_reply(
+            command_countdown(rest, true, ctx, t),
+            COUNTDOWN_COMMAND_INFO,
+        ),
+        "/since" => pattern_reply(
+            command_countdown(rest, false, ctx, t),
+            COUNTDOWN_COMMAND_INFO,
+        ),
+        "/epoch" => pattern_reply(command_epoch(rest, ctx), EPOCH_COMMAND_INFO),
+        "/meet" => pattern_reply(
+            command_meet(rest, &ctx.default_zones(), now, &format, t),
+            MEET_COMMAND_INFO,
+        ),
+        "/scan" => reply(command_scan(rest, ctx, t)),
+        "/lang" => reply(command_lang(rest, ctx)),
+        "/format" => reply(command_format(rest, ctx, t)),
+        // Chatter without a time gets no reply, even when a word reads as
+        // a timezone.
+        _ if !command.starts_with('/') && ctx.storage.chat_settings(ctx.chat_id).scan => {
+            if !mentions_time(text) {
+                return String::new().into();
+            }
+            reply_or_disambiguate(text, normal_message(text, ctx, t), t, |_| {
+                scan_message(text, ctx)
+            })
+        }
+        _ => reply_or_disambiguate(text, normal_message(text, ctx, t), t, |_| {
+            invalid_command(t)
+        }),
+    }
+}
+
+/// Converts every time mentioned in a chat message into the chat's zones.
+/// Returns an empty text, meaning no reply, when nothing was found.
+fn scan_message(text: &str, ctx: &Context) -> String {
+    let default_zones = ctx.default_zones();
+    let format = ctx.time_format();
+    find_mentions(text)
+        .iter()
+        .filter_map(|mention| {
+            let converter = mention
+                .converter(&default_zones)
+                .with_format(format)
+                .with_messages(ctx.language().messages());
+            let converted = converter.convert_time_between_timezones(ctx.now).ok()?;
+            converted.into_iter().next()
+        })
+        .join("\n")
+}
+
+fn normal_message(src_text: &str, ctx: &Context, t: &'static Messages) -> Result<String, BotError> {
+    // Unlike command arguments, plain messages are chatter, where "is" or
+    // "no" are words rather than countries.
+    if let Some(word) = src_text
+        .split_whitespace()
+        .find(|word| gazetteer::is_lowercase_country_code(word))
+    {
+        return Err(BotError::InvalidTimezone {
+            input: word.to_string(),
+            suggestions: Vec::new(),
+        });
+    }
+    let format = ctx.time_format();
+    match command_convert(src_text, &ctx.default_zones(), ctx.now, &format, t) {
+        Err(error @ BotError::AmbiguousTimezone { .. }) => Err(error),
+        result => result.or_else(|_| command_now(src_text, ctx.now, &format, t)),
     }
 }
 
-fn normal_message(src_text: &str, now: DateTime<Utc>) -> String {
-    command_convert(src_text, now)
-        .or_else(|_| command_now(src_text, now))
-        .unwrap_or_else(|_| invalid_command())
+/// Turns an ambiguous timezone error into a question offering every
+/// candidate, and any other error into a plain message via `on_error`.
+fn reply_or_disambiguate(
+    text: &str,
+    result: Result<String, BotError>,
+    t: &Messages,
+    on_error: impl FnOnce(BotError) -> String,
+) -> Reply {
+    match result {
+        Ok(reply) => reply.into(),
+        Err(error @ BotError::AmbiguousTimezone { .. }) => Reply {
+            options: disambiguations(text, &error)
+                .into_iter()
+                .map(|(tz, input)| ReplyOption {
+                    label: tz.to_string(),
+                    input,
+                })
+                .collect(),
+            text: fill(t.which_one, &[("error", &t.error(&error))]),
+        },
+        Err(error) => on_error(error).into(),
+    }
+}
+
+/// Rewrites `text` once per candidate of an ambiguous timezone error,
+/// replacing the ambiguous word with the candidate's full name.
+pub fn disambiguations(text: &str, error: &BotError) -> Vec<(Tz, String)> {
+    let BotError::AmbiguousTimezone { input, candidates } = error else {
+        return Vec::new();
+    };
+    candidates
+        .iter()
+        .map(|tz| {
+            let rewritten = text
+                .split_whitespace()
+                .map(|word| {
+                    if word.eq_ignore_ascii_case(input) {
+                        tz.name()
+                    } else {
+                        word
+                    }
+                })
+                .join(" ");
+            (*tz, rewritten)
+        })
+        .collect()
 }
 
 pub fn convert_from_input_or_default_timezones(
     src_text: &str,
-    default_timezones: &[Tz],
+    default_timezones: &[Zone],
 ) -> Result<Converter, BotError> {
-    let converter = match time::parse_time(src_text) {
-        Ok(time) => Converter::new(Some(time), default_timezones.to_vec()),
-        Err(_) => Converter::try_from(src_text)?,
-    };
+    let mut converter = Converter::try_from(src_text)?;
+    // With at most a source zone named, convert into the defaults as well.
+    if converter.timezones.len() <= 1 {
+        for zone in default_timezones {
+            if !converter.timezones.contains(zone) {
+                converter.timezones.push(zone.clone());
+            }
+        }
+    }
     Ok(converter)
 }
 
-const CONVERT_COMMAND_INFO: &str = "<time> <source_timezone> <target_timezone>";
+const CONVERT_COMMAND_INFO: &str = "[date] <time> <source_timezone> <target_timezone>";
+const SETTZ_COMMAND_INFO: &str = "<home_timezone> [favourite_timezones]";
+const ADDTZ_COMMAND_INFO: &str = "<timezones>";
+const MEET_COMMAND_INFO: &str = "[date] [working_hours] <timezones>";
+const DIFF_COMMAND_INFO: &str = "[date] <timezone> <timezones>";
+const COUNTDOWN_COMMAND_INFO: &str = "[date] <time> <timezone>";
+const EPOCH_COMMAND_INFO: &str = "[date] [time] [timezone]";
+const SCAN_COMMAND_INFO: &str = "[on|off]";
+const LANG_COMMAND_INFO: &str = "[en|pt|es|auto]";
+const FORMAT_COMMAND_INFO: &str =
+    "[chat] [12h|24h] [seconds] [offset] [weekday] [date] | [chat] reset";
 
-fn command_list() -> String {
+/// How far ahead /diff lists changes of the difference between zones.
+const DIFF_CHANGES_HORIZON: TimeDelta = TimeDelta::days(365);
+
+fn command_list(t: &Messages) -> String {
     format!(
-        "Commands accepted:\n\
+        "{}\n\
         /start\n\
         /now <timezone>\n\
-        /convert {CONVERT_COMMAND_INFO}"
+        /convert {CONVERT_COMMAND_INFO}\n\
+        /settz {SETTZ_COMMAND_INFO}\n\
+        /addtz {ADDTZ_COMMAND_INFO}\n\
+        /removetz {ADDTZ_COMMAND_INFO}\n\
+        /listtz\n\
+        /who\n\
+        /meet {MEET_COMMAND_INFO}\n\
+        /tzinfo <timezones>\n\
+        /diff {DIFF_COMMAND_INFO}\n\
+        /until {COUNTDOWN_COMMAND_INFO}\n\
+        /since {COUNTDOWN_COMMAND_INFO}\n\
+        /epoch {EPOCH_COMMAND_INFO}\n\
+        /scan {SCAN_COMMAND_INFO}\n\
+        /lang {LANG_COMMAND_INFO}\n\
+        /format {FORMAT_COMMAND_INFO}",
+        t.commands_accepted
     )
 }
 
-fn invalid_command() -> String {
-    format!("Invalid command.\n\n{}", command_list())
+fn invalid_command(t: &Messages) -> String {
+    format!("{}\n\n{}", t.invalid_command, command_list(t))
+}
+
+fn command_start(t: &Messages) -> String {
+    format!("{}\n\n{}", t.welcome, command_list(t))
 }
 
-fn command_start() -> String {
-    format!("Welcome!\n\n{}", command_list())
+/// E.g. "Usage: /diff [date] <timezone> <timezones>".
+fn usage(command: &str, info: &str, t: &Messages) -> String {
+    fill(t.usage, &[("command", &format!("{command} {info}"))])
 }
 
-fn command_now(timezone: &str, now: DateTime<Utc>) -> Result<String, BotError> {
+/// A short explanation followed by the command that helps, e.g. "You have
+/// no home timezone yet.\n\nUse /settz ...".
+fn hint(text: &str, command: &str, info: &str, t: &Messages) -> String {
+    let command = format!("{command} {info}");
+    format!(
+        "{text}\n\n{}",
+        fill(t.use_command, &[("command", &command)])
+    )
+}
+
+fn command_now(
+    timezone: &str,
+    now: DateTime<Utc>,
+    format: &TimeFormat,
+    t: &'static Messages,
+) -> Result<String, BotError> {
     let ret = Converter::try_from_only_timezones(timezone)?
+        .with_format(*format)
+        .with_messages(t)
         .now_in_timezones(now)
         .join(" - ");
     Ok(ret)
 }
 
-fn command_convert(input: &str, now: DateTime<Utc>) -> Result<String, BotError> {
-    let ret = Converter::try_from(input)?
+/// Converts `input`, falling back to `default_zones` when it names no
+/// timezone (e.g. a bare "15:00").
+fn command_convert(
+    input: &str,
+    default_zones: &[Zone],
+    now: DateTime<Utc>,
+    format: &TimeFormat,
+    t: &'static Messages,
+) -> Result<String, BotError> {
+    let ret = convert_from_input_or_default_timezones(input, default_zones)?
+        .with_format(*format)
+        .with_messages(t)
         .convert_time_between_timezones(now)?
         .into_iter()
         .next()
-        .unwrap_or_else(|| "No time to convert".to_string());
+        .unwrap_or_else(|| t.no_time_to_convert.to_string());
+    Ok(ret)
+}
+
+fn command_settz(input: &str, ctx: &Context, t: &Messages) -> Result<String, BotError> {
+    let settings = ctx.storage.user_settings(ctx.user_id);
+    if input.trim().is_empty() {
+        let Some(home) = &settings.home else {
+            return Ok(hint(t.no_home_timezone, "/settz", SETTZ_COMMAND_INFO, t));
+        };
+        return Ok(describe_user_settings(home, &settings.favourites, t));
+    }
+
+    let zones = Converter::try_from_only_timezones(input)?.timezones;
+    let mut names = zones.iter().map(Zone::to_string);
+    let home = names.next();
+    let favourites: Vec<String> = names.collect();
+    ctx.storage.update_user_settings(
+        ctx.user_id,
+        Box::new(|settings| {
+            settings.home = home.clone();
+            settings.favourites = favourites.clone();
+        }),
+    )?;
+    join_chat_team(ctx)?;
+    Ok(describe_user_settings(
+        &home.unwrap_or_default(),
+        &favourites,
+        t,
+    ))
+}
+
+fn describe_user_settings(home: &str, favourites: &[String], t: &Messages) -> String {
+    let home = fill(t.home_timezone, &[("home", &home)]);
+    if favourites.is_empty() {
+        home
+    } else {
+        let favourites = fill(t.favourites, &[("favourites", &favourites.join(", "))]);
+        format!("{home}\n{favourites}")
+    }
+}
+
+/// Chooses the reply language, or goes back to the Telegram locale with
+/// "auto".
+fn command_lang(input: &str, ctx: &Context) -> Result<String, BotError> {
+    let input = input.trim().to_lowercase();
+    let language = match input.as_str() {
+        "" => return Ok(ctx.language().messages().language_set.to_string()),
+        "auto" => None,
+        code => match Language::from_code(code) {
+            Some(language) => Some(language.code().to_string()),
+            None => {
+                let t = ctx.language().messages();
+                return Ok(usage("/lang", LANG_COMMAND_INFO, t));
+            }
+        },
+    };
+    let automatic = language.is_none();
+    ctx.storage.update_user_settings(
+        ctx.user_id,
+        Box::new(|settings| settings.language = language),
+    )?;
+    let t = ctx.language().messages();
+    Ok(if automatic {
+        t.language_auto.to_string()
+    } else {
+        t.language_set.to_string()
+    })
+}
+
+/// Chooses how times are written for the sender, or for everyone in the
+/// chat with "chat". "reset" goes back to the default.
+fn command_format(input: &str, ctx: &Context, t: &Messages) -> Result<String, BotError> {
+    let input = input.trim();
+    let (chat, options) = match input.split_once(' ') {
+        Some((first, rest)) if first.eq_ignore_ascii_case("chat") => (true, rest),
+        _ if input.eq_ignore_ascii_case("chat") => (true, ""),
+        _ => (false, input),
+    };
+    let format = match options.trim().to_lowercase().as_str() {
+        "" if chat => {
+            let format = ctx.storage.chat_settings(ctx.chat_id).format;
+            return Ok(fill(
+                t.chat_time_format,
+                &[("format", &format.unwrap_or_default())],
+            ));
+        }
+        "" => {
+            let format = ctx.time_format();
+            return Ok(fill(t.time_format, &[("format", &format)]));
+        }
+        "reset" => None,
+        options => match TimeFormat::parse(options) {
+            Some(format) => Some(format),
+            None => return Ok(usage("/format", FORMAT_COMMAND_INFO, t)),
+        },
+    };
+    let shown = format.unwrap_or_default();
+    if chat {
+        ctx.storage
+            .update_chat_settings(ctx.chat_id, Box::new(|settings| settings.format = format))?;
+        Ok(fill(t.chat_time_format, &[("format", &shown)]))
+    } else {
+        ctx.storage
+            .update_user_settings(ctx.user_id, Box::new(|settings| settings.format = format))?;
+        Ok(fill(t.time_format, &[("format", &shown)]))
+    }
+}
+
+fn command_addtz(input: &str, ctx: &Context, t: &Messages) -> Result<String, BotError> {
+    let added = Converter::try_from_only_timezones(input)?.timezones;
+    ctx.storage.update_chat_settings(
+        ctx.chat_id,
+        Box::new(|settings| {
+            for zone in added.iter().map(Zone::to_string) {
+                if !settings.zones.contains(&zone) {
+                    settings.zones.push(zone);
+                }
+            }
+        }),
+    )?;
+    Ok(command_listtz(ctx, t))
+}
+
+fn command_removetz(input: &str, ctx: &Context, t: &Messages) -> Result<String, BotError> {
+    let removed: Vec<String> = Converter::try_from_only_timezones(input)?
+        .timezones
+        .iter()
+        .map(Zone::to_string)
+        .collect();
+    ctx.storage.update_chat_settings(
+        ctx.chat_id,
+        Box::new(|settings| settings.zones.retain(|zone| !removed.contains(zone))),
+    )?;
+    Ok(command_listtz(ctx, t))
+}
+
+/// Turns looking for times in every chat message on or off.
+fn command_scan(input: &str, ctx: &Context, t: &Messages) -> Result<String, BotError> {
+    let scan = match input.trim().to_lowercase().as_str() {
+        "on" => Some(true),
+        "off" => Some(false),
+        "" => None,
+        _ => return Ok(usage("/scan", SCAN_COMMAND_INFO, t)),
+    };
+    if let Some(scan) = scan {
+        ctx.storage
+            .update_chat_settings(ctx.chat_id, Box::new(|settings| settings.scan = scan))?;
+    }
+    let scan = ctx.storage.chat_settings(ctx.chat_id).scan;
+    Ok(if scan { t.scan_on } else { t.scan_off }.to_string())
+}
+
+fn command_listtz(ctx: &Context, t: &Messages) -> String {
+    let settings = ctx.storage.chat_settings(ctx.chat_id);
+    if settings.zones.is_empty() {
+        hint(t.no_chat_timezones, "/addtz", ADDTZ_COMMAND_INFO, t)
+    } else {
+        fill(t.chat_timezones, &[("zones", &settings.zones.join(", "))])
+    }
+}
+
+/// Plans a meeting across the given zones, or the chat's defaults when none
+/// are named.
+fn command_meet(
+    input: &str,
+    default_zones: &[Zone],
+    now: DateTime<Utc>,
+    format: &TimeFormat,
+    t: &Messages,
+) -> Result<String, BotError> {
+    let mut planner = MeetingPlanner::try_from(input)?;
+    if planner.timezones.is_empty() {
+        planner.timezones = default_zones.to_vec();
+    }
+    planner.format = *format;
+    planner.describe(now, t)
+}
+
+/// Offset, DST state and surrounding offset changes of each zone.
+fn command_tzinfo(
+    input: &str,
+    default_zones: &[Zone],
+    now: DateTime<Utc>,
+    t: &Messages,
+) -> Result<String, BotError> {
+    let mut zones = Converter::try_from_only_timezones(input)?.timezones;
+    if zones.is_empty() {
+        zones = default_zones.to_vec();
+    }
+    if zones.is_empty() {
+        return Ok(usage("/tzinfo", "<timezones>", t));
+    }
+    let ret = zones
+        .iter()
+        .map(|tz| {
+            let time = now.with_timezone(tz);
+            let describe_change = |forward| match offset_change(tz, now, forward) {
+                Some(change) => describe_offset_change(&change, t),
+                None => t.no_offset_change.to_string(),
+            };
+            format!(
+                "{tz}\n\
+                {}: {} ({})\n\
+                {}: {}\n\
+                {}: {}\n\
+                {}: {}",
+                t.utc_offset,
+                time.offset().fix(),
+                time.offset(),
+                t.daylight_saving,
+                if is_dst(&time) { t.yes } else { t.no },
+                t.next_change,
+                describe_change(true),
+                t.previous_change,
+                describe_change(false),
+            )
+        })
+        .join("\n\n");
+    Ok(ret)
+}
+
+/// E.g. "2026-03-29 02:00, clocks go forward 1h", with the local time as read
+/// just before the change.
+fn describe_offset_change(change: &OffsetChange, t: &Messages) -> String {
+    let local = change.at.with_timezone(&change.before);
+    let delta = change.after.local_minus_utc() - change.before.local_minus_utc();
+    let template = if delta > 0 {
+        t.clocks_forward
+    } else {
+        t.clocks_back
+    };
+    fill(
+        template,
+        &[
+            ("when", &local.format("%Y-%m-%d %H:%M")),
+            ("amount", &format_offset_seconds(delta)),
+        ],
+    )
+}
+
+/// Time left until the target (`until`) or elapsed since it, followed by
+/// the target in the sender's zones. Without a date, the target is the next
+/// (or, for /since, the last) occurrence of the time.
+fn command_countdown(
+    input: &str,
+    until: bool,
+    ctx: &Context,
+    t: &Messages,
+) -> Result<String, BotError> {
+    let command = if until { "/until" } else { "/since" };
+    let converter = Converter::try_from(input)?;
+    // A range has no single moment to count to.
+    if converter.base_time.is_none() || converter.end_time.is_some() {
+        return Ok(usage(command, COUNTDOWN_COMMAND_INFO, t));
+    }
+    let mut zones = converter.timezones.clone();
+    for zone in ctx.user_zones() {
+        if !zones.contains(&zone) {
+            zones.push(zone);
+        }
+    }
+    let Some(src_tz) = zones.first() else {
+        return Ok(usage(command, COUNTDOWN_COMMAND_INFO, t));
+    };
+
+    let mut target = converter.time_in(src_tz, ctx.now)?;
+    if let (None, Some(time)) = (converter.base_date, converter.base_time) {
+        // Move by a calendar day, not 24 hours, so offset changes in
+        // between are accounted for.
+        let date = target.date_naive();
+        let other_day = if until && target < ctx.now {
+            date.succ_opt()
+        } else if !until && target > ctx.now {
+            date.pred_opt()
+        } else {
+            None
+        };
+        if let Some(day) = other_day {
+            target = time_with_timezone(&time, src_tz, day)?;
+        }
+    }
+    let format = ctx.time_format();
+    let label = format_time_with_timezone(
+        &target,
+        &TimeFormat {
+            date: true,
+            ..format
+        },
+        t,
+    );
+    let elapsed = ctx.now - target.to_utc();
+    let (template, duration) = match (until, elapsed > TimeDelta::zero()) {
+        (true, false) => (t.until, -elapsed),
+        (true, true) => (t.was_ago, elapsed),
+        (false, true) => (t.since, elapsed),
+        (false, false) => (t.is_away, -elapsed),
+    };
+    let heading = fill(
+        template,
+        &[
+            ("duration", &format_duration(duration, t)),
+            ("target", &label),
+        ],
+    );
+    let times = zones
+        .iter()
+        .map(|tz| {
+            let time = target.with_timezone(tz);
+            let days = (time.date_naive() - target.date_naive()).num_days();
+            format_time_with_timezone(&time, &format, t) + &format_day_shift(days)
+        })
+        .join(" - ");
+    Ok(format!("{heading}\n{times}"))
+}
+
+/// The Unix timestamp of a time read in the given zone, the sender's home
+/// zone or UTC, in that order. Without a time, the timestamp of now.
+fn command_epoch(input: &str, ctx: &Context) -> Result<String, BotError> {
+    let converter = Converter::try_from(input)?;
+    // A range has no single timestamp.
+    if converter.end_time.is_some() {
+        return Ok(usage(
+            "/epoch",
+            EPOCH_COMMAND_INFO,
+            ctx.language().messages(),
+        ));
+    }
+    let src_tz = converter
+        .timezones
+        .first()
+        .cloned()
+        .or_else(|| ctx.user_zones().into_iter().next())
+        .unwrap_or(Zone::Named(Tz::UTC));
+    let time = converter.time_in(&src_tz, ctx.now)?;
+    let format = TimeFormat {
+        date: true,
+        ..ctx.time_format()
+    };
+    Ok(format!(
+        "{}\n{}",
+        time.timestamp(),
+        format_time_with_timezone(&time, &format, ctx.language().messages())
+    ))
+}
+
+/// E.g. "3 days, 1 hour, 5 minutes", leaving out zero parts.
+fn format_duration(duration: TimeDelta, t: &Messages) -> String {
+    let parts = [
+        (duration.num_days(), &t.days),
+        (duration.num_hours() % 24, &t.hours),
+        (duration.num_minutes() % 60, &t.minutes),
+    ];
+    let ret = parts
+        .iter()
+        .filter(|(amount, _)| *amount != 0)
+        .map(|(amount, unit)| t.amount(*amount, unit))
+        .join(", ");
+    if ret.is_empty() {
+        t.amount(0, &t.minutes)
+    } else {
+        ret
+    }
+}
+
+/// How far the first zone is ahead of each other zone, at noon in the first
+/// zone on the given date or now, and when that is about to change.
+fn command_diff(
+    input: &str,
+    default_zones: &[Zone],
+    now: DateTime<Utc>,
+    t: &Messages,
+) -> Result<String, BotError> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let mut rest = tokens.as_slice();
+    let date = take_prefix(&mut rest, parse_date_prefix);
+    let mut zones = timezone_parser(rest)?;
+    if zones.len() <= 1 {
+        for zone in default_zones {
+            if !zones.contains(zone) {
+                zones.push(zone.clone());
+            }
+        }
+    }
+    let (first, others) = match zones.as_slice() {
+        [first, others @ ..] if !others.is_empty() => (first, others),
+        _ => return Ok(usage("/diff", DIFF_COMMAND_INFO, t)),
+    };
+
+    let moment = match date {
+        Some(date) => {
+            let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap_or_default();
+            let day = date.resolve(now.with_timezone(first).date_naive())?;
+            time_with_timezone(&noon, first, day)?.to_utc()
+        }
+        None => now,
+    };
+    let ret = others
+        .iter()
+        .map(|other| describe_offset_gap(first, other, moment, t))
+        .join("\n");
     Ok(ret)
 }
 
-fn convert_error(error: &BotError) -> String {
-    format!("{error}\n\nPlease follow the pattern below\n\n{CONVERT_COMMAND_INFO}")
+/// E.g. "CET is 4h ahead of BRT (5h from 2026-03-29)".
+fn describe_offset_gap(first: &Zone, other: &Zone, moment: DateTime<Utc>, t: &Messages) -> String {
+    let gap = |time: DateTime<Utc>| {
+        time.with_timezone(first).offset().fix().local_minus_utc()
+            - time.with_timezone(other).offset().fix().local_minus_utc()
+    };
+    let current = gap(moment);
+    let horizon = moment
+        .checked_add_signed(DIFF_CHANGES_HORIZON)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let mut changes: Vec<DateTime<Utc>> = Vec::new();
+    for tz in [first, other] {
+        let mut from = moment;
+        while let Some(change) = offset_change(tz, from, true).filter(|c| c.at <= horizon) {
+            from = change.at;
+            changes.push(change.at);
+        }
+    }
+    changes.sort();
+
+    let mut last = current;
+    let mut upcoming = Vec::new();
+    for at in changes {
+        let new_gap = gap(at);
+        if new_gap != last {
+            let amount = format_offset_seconds(new_gap);
+            let gap = match new_gap {
+                0 => t.gap_same.to_string(),
+                _ if new_gap.signum() == current.signum() => amount,
+                _ if new_gap > 0 => fill(t.gap_ahead, &[("amount", &amount)]),
+                _ => fill(t.gap_behind, &[("amount", &amount)]),
+            };
+            let date = at.with_timezone(first).format("%Y-%m-%d");
+            upcoming.push(fill(t.gap_from, &[("gap", &gap), ("date", &date)]));
+            last = new_gap;
+        }
+    }
+
+    let name = |tz: &Zone| format_timezone(&moment.with_timezone(tz));
+    let template = match current {
+        0 => t.same_time_as,
+        _ if current > 0 => t.ahead_of,
+        _ => t.behind,
+    };
+    let sentence = fill(
+        template,
+        &[
+            ("first", &name(first)),
+            ("other", &name(other)),
+            ("amount", &format_offset_seconds(current)),
+        ],
+    );
+    if upcoming.is_empty() {
+        sentence
+    } else {
+        format!("{sentence} ({})", upcoming.join(", "))
+    }
+}
+
+/// E.g. "1h", "30m" or "5h45m", ignoring the sign.
+fn format_offset_seconds(seconds: i32) -> String {
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    match (hours, minutes) {
+        (hours, 0) => format!("{hours}h"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h{minutes:02}m"),
+    }
+}
+
+/// Lists the chat's members to /who, replacing the name of a member who
+/// registers again.
+fn join_chat_team(ctx: &Context) -> Result<(), BotError> {
+    let member = ChatMember {
+        user_id: ctx.user_id,
+        name: ctx.user_name.clone(),
+    };
+    ctx.storage.update_chat_settings(
+        ctx.chat_id,
+        Box::new(|settings| {
+            match settings
+                .members
+                .iter_mut()
+                .find(|m| m.user_id == ctx.user_id)
+            {
+                Some(existing) => *existing = member,
+                None => settings.members.push(member),
+            }
+        }),
+    )
+}
+
+/// The current local time of every member who registered in this chat,
+/// flagging those outside working hours.
+fn command_who(ctx: &Context, t: &'static Messages) -> String {
+    let members: Vec<(String, Zone)> = ctx
+        .storage
+        .chat_settings(ctx.chat_id)
+        .members
+        .into_iter()
+        .filter_map(|member| {
+            let home = ctx.storage.user_settings(member.user_id).home?;
+            Some((member.name, parse_zone(&home).ok()?))
+        })
+        .collect();
+    if members.is_empty() {
+        return hint(t.no_members, "/settz", SETTZ_COMMAND_INFO, t);
+    }
+
+    let (names, zones): (Vec<String>, Vec<Zone>) = members.into_iter().unzip();
+    let (start, end) = WORKING_HOURS;
+    let outside = fill(
+        t.outside_hours,
+        &[("hours", &format!("{start:02}:00-{end:02}:00"))],
+    );
+    let converter = Converter::new(None, zones)
+        .with_format(ctx.time_format())
+        .with_messages(t);
+    converter
+        .now_in_timezones(ctx.now)
+        .zip(&names)
+        .zip(&converter.timezones)
+        .map(|((time, name), zone)| {
+            if is_working_hours(&ctx.now.with_timezone(zone)) {
+                format!("{name}: {time}")
+            } else {
+                format!("{name}: {time} ({outside})")
+            }
+        })
+        .join("\n")
+}
+
+fn error_message(error: &BotError, t: &Messages) -> String {
+    let message = t.error(error);
+    match error {
+        BotError::InvalidTimezone { suggestions, .. } if !suggestions.is_empty() => {
+            let suggestions = suggestions.join(", ");
+            let did_you_mean = fill(t.did_you_mean, &[("suggestions", &suggestions)]);
+            format!("{message}\n\n{did_you_mean}")
+        }
+        _ => message,
+    }
+}
+
+/// The error followed by the pattern the command's input should follow.
+fn pattern_error(error: &BotError, info: &str, t: &Messages) -> String {
+    format!(
+        "{}\n\n{}\n\n{info}",
+        error_message(error, t),
+        t.follow_pattern
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{NaiveTime, TimeZone};
+    use crate::i18n::ENGLISH;
+    use crate::storage::JsonStorage;
+    use chrono::TimeZone;
 
     fn winter_now() -> DateTime<Utc> {
         Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap()
@@ -85,42 +926,121 @@ mod tests {
         Utc.with_ymd_and_hms(2026, 7, 15, 12, 0, 0).unwrap()
     }
 
+    fn process(text: &str, now: DateTime<Utc>) -> Reply {
+        let storage = JsonStorage::in_memory();
+        process_input(text, &context(&storage, now))
+    }
+
+    fn context(storage: &JsonStorage, now: DateTime<Utc>) -> Context<'_> {
+        Context {
+            storage,
+            user_id: 1,
+            user_name: "John".into(),
+            language_code: None,
+            chat_id: -100,
+            now,
+        }
+    }
+
     #[test]
     fn test_convert_time_brt_cet() {
-        let result = command_convert("12:00 BRT CET", winter_now());
+        let result = command_convert(
+            "12:00 BRT CET",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "12:00 BRT - 16:00 CET");
 
-        let result = command_convert("12:00 BRT CET", summer_now());
+        let result = command_convert(
+            "12:00 BRT CET",
+            &[],
+            summer_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "12:00 BRT - 17:00 CET");
     }
 
     #[test]
     fn test_convert_time_utc_brl() {
-        let result = command_convert("12:00 UTC BRT", winter_now());
+        let result = command_convert(
+            "12:00 UTC BRT",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "12:00 UTC - 09:00 BRT");
     }
 
     #[test]
     fn test_convert_time_one_digit() {
-        let result = command_convert("1:00 BRT CET", winter_now());
+        let result = command_convert(
+            "1:00 BRT CET",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "01:00 BRT - 05:00 CET");
     }
 
     #[test]
     fn test_convert_time_minimal() {
-        let result = command_convert("2 BRT CET", winter_now());
+        let result = command_convert(
+            "2 BRT CET",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "02:00 BRT - 06:00 CET");
     }
 
+    #[test]
+    fn test_convert_time_meridiem() {
+        let result = command_convert(
+            "3pm BRT CET",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
+        assert_eq!(result.unwrap(), "15:00 BRT - 19:00 CET");
+
+        let result = command_convert(
+            "12am UTC BRT",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
+        assert_eq!(result.unwrap(), "00:00 UTC - 21:00 BRT (-1)");
+    }
+
     #[test]
     fn test_convert_time_multiple_spaces() {
-        let result = command_convert("12:00    BRT     RO    ", winter_now());
+        let result = command_convert(
+            "12:00    BRT     RO    ",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "12:00 BRT - 17:00 Europe/Bucharest");
     }
 
     #[test]
     fn test_convert_time_missing_target_tz() {
-        let result = command_convert("12:00 UTC", winter_now());
+        let result = command_convert(
+            "12:00 UTC",
+            &[],
+            winter_now(),
+            &TimeFormat::default(),
+            &ENGLISH,
+        );
         assert_eq!(result.unwrap(), "12:00 UTC");
     }
 
@@ -128,55 +1048,559 @@ mod tests {
     fn test_convert_time_nonexistent() {
         // CET skips 02:00-03:00 on 2026-03-29 (spring forward).
         let now = Utc.with_ymd_and_hms(2026, 3, 29, 12, 0, 0).unwrap();
-        let result = command_convert("2:30 CET BRT", now);
+        let result = command_convert("2:30 CET BRT", &[], now, &TimeFormat::default(), &ENGLISH);
         assert_eq!(
             result,
             Err(BotError::NonexistentTime {
                 time: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
-                tz: Tz::CET,
+                tz: Tz::CET.into(),
             })
         );
     }
 
     #[test]
     fn test_process_command_start() {
-        let result = process_input("/start", winter_now());
-        assert_eq!(result, command_start());
+        let result = process("/start", winter_now());
+        assert_eq!(result, command_start(&ENGLISH));
     }
 
     #[test]
     fn test_process_command_now() {
-        let result = process_input("/now utc", winter_now());
-        assert_eq!(result, command_now("utc", winter_now()).unwrap());
+        let result = process("/now utc", winter_now());
+        assert_eq!(
+            result,
+            command_now("utc", winter_now(), &TimeFormat::default(), &ENGLISH).unwrap()
+        );
     }
 
     #[test]
     fn test_process_command_now_multiple_spaces() {
-        let result = process_input("/now   utc    ", winter_now());
-        assert_eq!(result, command_now("utc", winter_now()).unwrap());
+        let result = process("/now   utc    ", winter_now());
+        assert_eq!(
+            result,
+            command_now("utc", winter_now(), &TimeFormat::default(), &ENGLISH).unwrap()
+        );
     }
 
     #[test]
     fn test_process_command_convert() {
-        let result = process_input("/convert 12:00 UTC BRT", winter_now());
+        let result = process("/convert 12:00 UTC BRT", winter_now());
         assert_eq!(
             result,
-            command_convert("12:00 UTC BRT", winter_now()).unwrap()
+            command_convert(
+                "12:00 UTC BRT",
+                &[],
+                winter_now(),
+                &TimeFormat::default(),
+                &ENGLISH
+            )
+            .unwrap()
         );
     }
 
     #[test]
     fn test_process_command_with_h_convert() {
-        let result = process_input("/convert 12h UTC BRT", winter_now());
+        let result = process("/convert 12h UTC BRT", winter_now());
+        assert_eq!(
+            result,
+            command_convert(
+                "12:00 UTC BRT",
+                &[],
+                winter_now(),
+                &TimeFormat::default(),
+                &ENGLISH
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_process_ambiguous_timezone() {
+        let result = process("/convert 12:00 IST BRT", winter_now());
+        assert_eq!(
+            result.text,
+            "IST is ambiguous, it could mean Europe/Dublin, Asia/Kolkata, Asia/Jerusalem. \
+            Which one did you mean?"
+        );
+        assert_eq!(
+            result.options[1],
+            ReplyOption {
+                label: "Asia/Kolkata".into(),
+                input: "/convert 12:00 Asia/Kolkata BRT".into(),
+            }
+        );
+
+        let result = process(&result.options[1].input, winter_now());
+        assert_eq!(result, "12:00 Asia/Kolkata - 03:30 BRT".to_string());
+    }
+
+    #[test]
+    fn test_process_ambiguous_timezone_without_command() {
+        let result = process("12:00 BRT ist", winter_now());
+        assert_eq!(result.options.len(), 3);
+        assert_eq!(result.options[0].input, "12:00 BRT Europe/Dublin");
+    }
+
+    #[test]
+    fn test_process_command_now_suggests_timezone() {
+        let result = process("/now Europe/Amsterdm", winter_now());
+        assert_eq!(
+            result,
+            "Invalid timezone: Europe/Amsterdm\n\nDid you mean Europe/Amsterdam?".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_command_convert_suggests_timezone() {
+        let result = process("/convert 12:00 tokio BRT", winter_now());
+        assert!(
+            result
+                .text
+                .starts_with("Invalid timezone: tokio\n\nDid you mean Asia/Tokyo?"),
+            "{}",
+            result.text
+        );
+    }
+
+    #[test]
+    fn test_process_command_settz() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+
+        let result = process_input("/settz", &ctx);
+        assert!(result.text.starts_with("You have no home timezone yet."));
+
+        let result = process_input("/settz CET new york BRT", &ctx);
         assert_eq!(
             result,
-            command_convert("12:00 UTC BRT", winter_now()).unwrap()
+            "Home timezone: CET\nFavourites: America/New_York, America/Sao_Paulo".to_string()
+        );
+        assert_eq!(
+            process_input("/settz", &ctx).text,
+            "Home timezone: CET\nFavourites: America/New_York, America/Sao_Paulo"
+        );
+    }
+
+    #[test]
+    fn test_process_bare_time_uses_user_zones() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/settz CET BRT", &ctx);
+
+        let result = process_input("15:00", &ctx);
+        assert_eq!(result, "15:00 CET - 11:00 BRT".to_string());
+    }
+
+    #[test]
+    fn test_process_chat_roster() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+
+        assert!(
+            process_input("/listtz", &ctx)
+                .text
+                .starts_with("This chat has no timezones yet.")
+        );
+        assert_eq!(
+            process_input("/addtz CET BRT tokyo", &ctx),
+            "Timezones for this chat: CET, America/Sao_Paulo, Asia/Tokyo".to_string()
+        );
+        assert_eq!(
+            process_input("/removetz tokyo", &ctx),
+            "Timezones for this chat: CET, America/Sao_Paulo".to_string()
+        );
+        assert_eq!(
+            process_input("/listtz", &ctx),
+            "Timezones for this chat: CET, America/Sao_Paulo".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_bare_time_uses_chat_roster() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/settz UTC", &ctx);
+        process_input("/addtz CET BRT", &ctx);
+
+        assert_eq!(
+            process_input("15:00", &ctx),
+            "15:00 CET - 11:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("15:00 UTC", &ctx),
+            "15:00 UTC - 16:00 CET - 12:00 BRT".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_who() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        assert!(
+            process_input("/who", &ctx)
+                .text
+                .starts_with("No one has registered in this chat yet.")
+        );
+
+        process_input("/settz CET", &ctx);
+        let ana = Context {
+            user_id: 2,
+            user_name: "Ana".into(),
+            ..context(&storage, winter_now())
+        };
+        process_input("/settz BRT", &ana);
+        let kenji = Context {
+            user_id: 3,
+            user_name: "Kenji".into(),
+            ..context(&storage, winter_now())
+        };
+        process_input("/settz tokyo", &kenji);
+
+        assert_eq!(
+            process_input("/team", &ctx),
+            "John: 13:00 CET\nAna: 09:00 BRT\nKenji: 21:00 Asia/Tokyo (outside 09:00-18:00)"
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_meet() {
+        assert_eq!(
+            process("/meet CET BRT", winter_now()),
+            "Everyone is within working hours:\n12:00-17:00 UTC - 13:00-18:00 CET - 09:00-14:00 BRT".to_string()
+        );
+
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/addtz CET BRT", &ctx);
+        assert_eq!(
+            process_input("/meet 10-16", &ctx),
+            "Everyone is within working hours:\n13:00-15:00 UTC - 14:00-16:00 CET - 10:00-12:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("/meet 9-9", &ctx),
+            format!("Invalid time: 9-9\n\nPlease follow the pattern below\n\n{MEET_COMMAND_INFO}")
+        );
+    }
+
+    #[test]
+    fn test_process_tzinfo() {
+        assert_eq!(
+            process("/tzinfo CET", winter_now()),
+            "CET\n\
+            UTC offset: +01:00 (CET)\n\
+            Daylight saving: no\n\
+            Next change: 2026-03-29 02:00, clocks go forward 1h\n\
+            Previous change: 2025-10-26 03:00, clocks go back 1h"
+                .to_string()
+        );
+        assert_eq!(
+            process("/tzinfo BRT", summer_now()),
+            "America/Sao_Paulo\n\
+            UTC offset: -03:00 (-03)\n\
+            Daylight saving: no\n\
+            Next change: none in two years\n\
+            Previous change: none in two years"
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_diff() {
+        assert_eq!(
+            process("/diff CET BRT", winter_now()),
+            "CET is 4h ahead of BRT (5h from 2026-03-29, 4h from 2026-10-25)".to_string()
+        );
+        assert_eq!(
+            process("/diff 01/05 CET BRT tokyo", winter_now()),
+            "CET is 5h ahead of BRT (4h from 2026-10-25, 5h from 2027-03-28)\n\
+            CET is 7h behind Asia/Tokyo (8h from 2026-10-25, 7h from 2027-03-28)"
+                .to_string()
+        );
+        assert_eq!(
+            process("/diff UTC +05:45", winter_now()),
+            "UTC is 5h45m behind +05:45".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_diff_near_the_end_of_time() {
+        assert_eq!(
+            process("/diff 31/12/262142 CET BRT", winter_now()),
+            "CET is 4h ahead of BRT".to_string()
+        );
+        assert_eq!(
+            process("/diff 01/06/262142 CET BRT", winter_now()),
+            "CET is 4h ahead of BRT".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_until_and_since() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/settz BRT", &ctx);
+
+        assert_eq!(
+            process_input("/until 9-17 UTC", &ctx),
+            format!("Usage: /until {COUNTDOWN_COMMAND_INFO}")
+        );
+
+        assert_eq!(
+            process_input("/until 2026-12-31 23:59 CET", &ctx),
+            "350 days, 10 hours, 59 minutes until 2026-12-31 23:59 CET\n\
+            23:59 CET - 19:59 BRT"
+                .to_string()
+        );
+        assert_eq!(
+            process_input("/until 9am CET", &ctx),
+            "20 hours until 2026-01-16 09:00 CET\n09:00 CET - 05:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("/since 9am CET", &ctx),
+            "4 hours since 2026-01-15 09:00 CET\n09:00 CET - 05:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("/until 2026-01-01 00:00 BRT", &ctx),
+            "2026-01-01 00:00 BRT was 14 days, 9 hours ago\n00:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("/since 22:00 UTC", &ctx),
+            "14 hours since 2026-01-14 22:00 UTC\n22:00 UTC - 19:00 BRT".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_until_across_offset_change() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap();
+        assert_eq!(
+            process("/until 9am CET", now),
+            "19 hours until 2026-03-29 09:00 CET\n09:00 CET".to_string()
+        );
+        let now = Utc.with_ymd_and_hms(2026, 3, 29, 7, 0, 0).unwrap();
+        assert_eq!(
+            process("/since 10am CET", now),
+            "22 hours since 2026-03-28 10:00 CET\n10:00 CET".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_countdown_and_epoch_errors_show_own_pattern() {
+        for (text, info) in [
+            ("/until 9am Nowhere", COUNTDOWN_COMMAND_INFO),
+            ("/since 9am Nowhere", COUNTDOWN_COMMAND_INFO),
+            ("/epoch 9am Nowhere", EPOCH_COMMAND_INFO),
+        ] {
+            let reply = process(text, winter_now());
+            assert!(reply.text.ends_with(&format!("\n\n{info}")), "{reply:?}");
+        }
+    }
+
+    #[test]
+    fn test_process_epoch() {
+        assert_eq!(
+            process("/epoch 9-17 UTC", winter_now()),
+            format!("Usage: /epoch {EPOCH_COMMAND_INFO}")
+        );
+        assert_eq!(
+            process("/epoch 2026-01-15 13:00 CET", summer_now()),
+            "1768478400\n2026-01-15 13:00 CET".to_string()
+        );
+        assert_eq!(
+            process("/epoch", winter_now()),
+            "1768478400\n2026-01-15 12:00 UTC".to_string()
+        );
+        assert_eq!(
+            process("1768478400 BRT", winter_now()),
+            "2026-01-15 09:00 BRT".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_scan() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/addtz CET BRT", &ctx);
+        let message = "let's sync at 3pm UTC tomorrow, or 10:30 BRT";
+        assert!(
+            process_input(message, &ctx)
+                .text
+                .starts_with("Invalid command.")
+        );
+
+        assert_eq!(
+            process_input("/scan on", &ctx),
+            "Looking for times in every message of this chat.".to_string()
+        );
+        assert_eq!(
+            process_input(message, &ctx),
+            "15:00 UTC - 16:00 CET - 12:00 BRT\n10:30 BRT - 14:30 CET".to_string()
+        );
+        assert_eq!(
+            process_input("15:00 CET", &ctx),
+            "15:00 CET - 11:00 BRT".to_string()
+        );
+        assert_eq!(process_input("nothing to see here", &ctx), String::new());
+        assert_eq!(process_input("no", &ctx), String::new());
+        assert_eq!(process_input("in", &ctx), String::new());
+        assert_eq!(process_input("UTC", &ctx), String::new());
+        // Bare times still convert between the chat's zones.
+        assert_eq!(
+            process_input("15:00", &ctx),
+            "15:00 CET - 11:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("3pm", &ctx),
+            "15:00 CET - 11:00 BRT".to_string()
+        );
+        assert_eq!(
+            process_input("noon", &ctx),
+            "12:00 CET - 08:00 BRT".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_localized_by_language_code() {
+        let storage = JsonStorage::in_memory();
+        let ctx = Context {
+            language_code: Some("pt-br".into()),
+            ..context(&storage, winter_now())
+        };
+        assert!(
+            process_input("/start", &ctx)
+                .text
+                .starts_with("Bem-vindo!\n\nComandos aceitos:")
+        );
+        assert_eq!(
+            process_input("/now Nowhere", &ctx),
+            "Fuso horário inválido: Nowhere".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_lang_override() {
+        let storage = JsonStorage::in_memory();
+        let ctx = Context {
+            language_code: Some("pt-br".into()),
+            ..context(&storage, winter_now())
+        };
+        assert_eq!(
+            process_input("/lang es", &ctx),
+            "Las respuestas serán en español.".to_string()
+        );
+        assert_eq!(
+            process_input("/until 20:00 UTC", &ctx),
+            "8 horas hasta 2026-01-15 20:00 UTC\n20:00 UTC".to_string()
+        );
+        assert_eq!(
+            process_input("/lang auto", &ctx),
+            "As respostas seguirão o idioma do seu Telegram.".to_string()
+        );
+        assert_eq!(
+            process_input("/lang klingon", &ctx),
+            "Uso: /lang [en|pt|es|auto]".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_format() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        assert_eq!(
+            process_input("/format 12h offset", &ctx),
+            "Time format: 12h offset".to_string()
+        );
+        assert_eq!(
+            process_input("/now BRT CET", &ctx),
+            "9:00 AM BRT (UTC-3) - 1:00 PM CET (UTC+1)".to_string()
+        );
+        assert_eq!(
+            process_input("/convert 15:00 BRT CET", &ctx),
+            "3:00 PM BRT (UTC-3) - 7:00 PM CET (UTC+1)".to_string()
+        );
+        assert_eq!(
+            process_input("/format chat weekday", &ctx),
+            "Time format for this chat: 24h weekday".to_string()
+        );
+        assert_eq!(
+            process_input("/convert 23:00 BRT CET", &ctx),
+            "Thu 23:00 BRT - Fri 03:00 CET".to_string()
+        );
+        assert_eq!(
+            process_input("/format", &ctx),
+            "Time format: 24h weekday".to_string()
+        );
+        assert_eq!(
+            process_input("/format chat", &ctx),
+            "Time format for this chat: 24h weekday".to_string()
+        );
+        assert_eq!(
+            process_input("/format chat reset", &ctx),
+            "Time format for this chat: 24h".to_string()
+        );
+        assert_eq!(
+            process_input("/format", &ctx),
+            "Time format: 12h offset".to_string()
+        );
+        assert_eq!(
+            process_input("/format chat", &ctx),
+            "Time format for this chat: 24h".to_string()
+        );
+        assert_eq!(
+            process_input("/format 13h", &ctx),
+            format!("Usage: /format {FORMAT_COMMAND_INFO}")
+        );
+    }
+
+    #[test]
+    fn test_process_everyday_words_are_not_zones() {
+        let reply = process("is it", winter_now());
+        assert!(reply.text.starts_with("Invalid command."), "{reply:?}");
+        assert_eq!(
+            process("/convert 12:00 br nl", winter_now()),
+            "12:00 BRT - 16:00 Europe/Amsterdam".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_localized_time_words() {
+        let storage = JsonStorage::in_memory();
+        let ctx = context(&storage, winter_now());
+        process_input("/lang pt", &ctx);
+        assert_eq!(
+            process_input("/convert 22:00-06:00 UTC", &ctx),
+            "22:00-06:00 UTC (passa da meia-noite)".to_string()
+        );
+        process_input("/format weekday", &ctx);
+        assert_eq!(
+            process_input("/convert 23:00 BRT CET", &ctx),
+            "qui 23:00 BRT - sex 03:00 CET".to_string()
+        );
+    }
+
+    #[test]
+    fn test_process_invalid_date() {
+        let reply = process("/convert 2026-02-30 12:00 UTC", winter_now());
+        assert!(
+            reply.text.starts_with("Invalid date: 2026-02-30"),
+            "{reply:?}"
+        );
+    }
+
+    #[test]
+    fn test_process_huge_relative_time() {
+        let reply = process("/convert in 2147483647d UTC", winter_now());
+        assert!(
+            reply.text.starts_with("Invalid time: in 2147483647d"),
+            "{reply:?}"
         );
+        let reply = process("in 999999999 days UTC", winter_now());
+        assert!(reply.text.starts_with("Invalid command"), "{reply:?}");
     }
 
     #[test]
     fn test_process_command_invalid() {
-        let result = process_input("invalid", winter_now());
-        assert_eq!(result, invalid_command());
+        let result = process("invalid", winter_now());
+        assert_eq!(result, invalid_command(&ENGLISH));
     }
 }
@@ -1,25 +1,85 @@
-use chrono::{DateTime, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Offset, TimeDelta, Utc};
 use chrono_tz::Tz;
-use itertools::Itertools;
 
 use crate::error::BotError;
-use crate::time::{format_time_with_timezone, parse_time, parse_tz, time_with_timezone};
+use crate::i18n::{ENGLISH, Messages};
+use crate::time::{
+    DateSpec, TimeFormat, format_day_shift, format_time_range_with_timezone,
+    format_time_with_timezone, looks_like_date, parse_date_prefix, parse_epoch, parse_iso_datetime,
+    parse_relative_prefix, parse_time_prefix, parse_time_range_prefix, parse_zone_prefix,
+    time_with_timezone,
+};
+use crate::zone::Zone;
 
 pub struct Converter {
+    /// An exact moment, e.g. from a Unix timestamp, overriding the other
+    /// time fields.
+    pub instant: Option<DateTime<Utc>>,
     pub base_time: Option<NaiveTime>,
-    pub timezones: Vec<Tz>,
+    pub base_date: Option<DateSpec>,
+    pub shift: Option<TimeDelta>,
+    pub end_time: Option<NaiveTime>,
+    pub timezones: Vec<Zone>,
+    pub format: TimeFormat,
+    /// Language of words in the output, such as weekday names.
+    pub messages: &'static Messages,
+    /// The words the time was given in, quoted when it cannot be converted.
+    pub input: String,
 }
 
 impl Converter {
-    pub fn new(base_time: Option<NaiveTime>, timezones: Vec<Tz>) -> Self {
+    pub fn new(base_time: Option<NaiveTime>, timezones: Vec<Zone>) -> Self {
         Self {
+            instant: None,
             base_time,
+            base_date: None,
+            shift: None,
+            end_time: None,
             timezones,
+            format: TimeFormat::default(),
+            messages: &ENGLISH,
+            input: String::new(),
         }
     }
 
+    pub fn with_instant(mut self, instant: Option<DateTime<Utc>>) -> Self {
+        self.instant = instant;
+        self
+    }
+
+    pub fn with_date(mut self, base_date: Option<DateSpec>) -> Self {
+        self.base_date = base_date;
+        self
+    }
+
+    pub fn with_shift(mut self, shift: Option<TimeDelta>) -> Self {
+        self.shift = shift;
+        self
+    }
+
+    pub fn with_format(mut self, format: TimeFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_messages(mut self, messages: &'static Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    pub fn with_input(mut self, input: String) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn with_end_time(mut self, end_time: Option<NaiveTime>) -> Self {
+        self.end_time = end_time;
+        self
+    }
+
     pub fn try_from_only_timezones(src_text: &str) -> Result<Self, BotError> {
-        let timezones = timezone_parser(src_text.split_whitespace())?;
+        let tokens: Vec<&str> = src_text.split_whitespace().collect();
+        let timezones = timezone_parser(&tokens)?;
         Ok(Self::new(None, timezones))
     }
 
@@ -34,24 +94,80 @@ impl Converter {
     }
 
     pub fn now_in_timezones(&self, now: DateTime<Utc>) -> impl Iterator<Item = String> + '_ {
-        self.timezones
-            .iter()
-            .map(move |tz| format_time_with_timezone(&now.with_timezone(tz)))
+        self.timezones.iter().map(move |tz| {
+            format_time_with_timezone(&now.with_timezone(tz), &self.format, self.messages)
+        })
     }
 
-    fn convert_from_timezone(&self, src_tz: &Tz, now: DateTime<Utc>) -> Result<String, BotError> {
-        let src_time = match &self.base_time {
-            Some(time) => time_with_timezone(time, src_tz, now)?,
-            None => now.with_timezone(src_tz),
-        };
-        let mut times = vec![src_time];
-        for dst_tz in &self.timezones {
-            if src_tz == dst_tz {
-                continue;
+    /// The calendar date conversions are anchored to in `tz`: the requested
+    /// date if any, otherwise today.
+    fn date_in(&self, tz: &Zone, now: DateTime<Utc>) -> Result<NaiveDate, BotError> {
+        let today = now.with_timezone(tz).date_naive();
+        match &self.base_date {
+            Some(date) => date.resolve(today),
+            None => Ok(today),
+        }
+    }
+
+    /// The moment this converter refers to, read in `src_tz`.
+    pub fn time_in(&self, src_tz: &Zone, now: DateTime<Utc>) -> Result<DateTime<Zone>, BotError> {
+        if let Some(instant) = self.instant {
+            return Ok(instant.with_timezone(src_tz));
+        }
+        match &self.base_time {
+            Some(time) => time_with_timezone(time, src_tz, self.date_in(src_tz, now)?),
+            None => {
+                let shift = self.shift.unwrap_or_default();
+                let time = now
+                    .checked_add_signed(shift)
+                    .ok_or_else(|| BotError::InvalidTime(self.input.clone()))?;
+                Ok(time.with_timezone(src_tz))
             }
-            times.push(src_time.with_timezone(dst_tz));
         }
-        Ok(times.iter().map(format_time_with_timezone).join(" - "))
+    }
+
+    fn convert_from_timezone(&self, src_tz: &Zone, now: DateTime<Utc>) -> Result<String, BotError> {
+        let src_time = self.time_in(src_tz, now)?;
+        let src_end = match &self.end_time {
+            Some(end) => Some(end_of_range(end, &src_time)?),
+            None => None,
+        };
+        let zones = std::iter::once(src_tz).chain(self.timezones.iter().filter(|tz| *tz != src_tz));
+        zones
+            .map(|tz| {
+                let time = src_time.with_timezone(tz);
+                // Near the end of chrono's range the local time may not exist.
+                for moment in std::iter::once(&time).chain(&src_end) {
+                    if moment
+                        .naive_utc()
+                        .checked_add_offset(moment.offset().fix())
+                        .is_none()
+                    {
+                        return Err(BotError::InvalidTime(self.input.clone()));
+                    }
+                }
+                let mut format = self.format;
+                // A timestamp can be any day, so spell out the date.
+                format.date |= self.instant.is_some() && tz == src_tz;
+                // The weekday may differ between zones.
+                format.weekday |= self.base_date.is_some_and(|date| date.is_weekday());
+                let formatted = match &src_end {
+                    Some(end) => format_time_range_with_timezone(
+                        &time,
+                        &end.with_timezone(tz),
+                        &format,
+                        self.messages,
+                    ),
+                    None => format_time_with_timezone(&time, &format, self.messages),
+                };
+                if format.date || format.weekday {
+                    return Ok(formatted);
+                }
+                let days = (time.date_naive() - src_time.date_naive()).num_days();
+                Ok(formatted + &format_day_shift(days))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join(" - "))
     }
 }
 
@@ -60,35 +176,112 @@ impl TryFrom<&str> for Converter {
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
         let split_values: Vec<&str> = input.split_whitespace().collect();
+        let mut rest = split_values.as_slice();
 
-        let (base_time, timezone_start_index) = if split_values.is_empty() {
-            (None, 0)
-        } else if split_values[0] == "now" {
-            (None, 1)
-        } else {
-            match parse_time(split_values[0]) {
-                Ok(time) => (Some(time), 1),
-                Err(_) => (None, 0),
+        if let Some(datetime) = take_prefix(&mut rest, |tokens| {
+            Some((parse_iso_datetime(tokens[0])?, 1))
+        }) {
+            let mut timezones = vec![datetime.timezone()];
+            for zone in timezone_parser(rest)? {
+                if !timezones.contains(&zone) {
+                    timezones.push(zone);
+                }
             }
+            return Ok(Self::new(None, timezones).with_instant(Some(datetime.to_utc())));
+        }
+        if let Some(instant) = take_prefix(&mut rest, |tokens| Some((parse_epoch(tokens[0])?, 1))) {
+            let mut timezones = timezone_parser(rest)?;
+            if timezones.is_empty() {
+                timezones.push(Zone::from(Tz::UTC));
+            }
+            return Ok(Self::new(None, timezones).with_instant(Some(instant)));
+        }
+
+        let base_date = take_prefix(&mut rest, parse_date_prefix);
+        if base_date.is_none()
+            && let Some(token) = rest.first().filter(|token| looks_like_date(token))
+        {
+            return Err(BotError::InvalidDate(token.to_string()));
+        }
+        let mut shift = None;
+        let mut end_time = None;
+        let base_time = if rest.first() == Some(&"now") {
+            rest = &rest[1..];
+            None
+        } else if let Some(delta) = take_prefix(&mut rest, parse_relative_prefix) {
+            shift = Some(delta);
+            None
+        } else if let Some((start, end)) = take_prefix(&mut rest, parse_time_range_prefix) {
+            end_time = Some(end);
+            Some(start)
+        } else {
+            take_prefix(&mut rest, parse_time_prefix)
         };
+        if base_date.is_some() && base_time.is_none() {
+            let token = rest.first().copied().unwrap_or_default();
+            return Err(BotError::InvalidTime(token.to_string()));
+        }
 
-        let timezones = timezone_parser(split_values.into_iter().skip(timezone_start_index))?;
-        Ok(Self::new(base_time, timezones))
+        let input = split_values[..split_values.len() - rest.len()].join(" ");
+        let timezones = timezone_parser(rest)?;
+        Ok(Self::new(base_time, timezones)
+            .with_date(base_date)
+            .with_shift(shift)
+            .with_end_time(end_time)
+            .with_input(input))
     }
 }
 
-fn timezone_parser<'a>(input: impl Iterator<Item = &'a str>) -> Result<Vec<Tz>, BotError> {
-    input.unique().map(parse_tz).collect()
+/// Places the end of a range on the same day as `start`, or on the next day
+/// when it is not after the start (e.g. "22-6").
+pub(crate) fn end_of_range(
+    end: &NaiveTime,
+    start: &DateTime<Zone>,
+) -> Result<DateTime<Zone>, BotError> {
+    let mut date = start.date_naive();
+    if *end <= start.time() {
+        date = date.succ_opt().unwrap_or(date);
+    }
+    time_with_timezone(end, &start.timezone(), date)
+}
+
+/// Applies `parse` to the start of `rest`, advancing past the tokens it used.
+pub(crate) fn take_prefix<T>(
+    rest: &mut &[&str],
+    parse: impl Fn(&[&str]) -> Option<(T, usize)>,
+) -> Option<T> {
+    if rest.is_empty() {
+        return None;
+    }
+    let (value, len) = parse(rest)?;
+    *rest = &rest[len..];
+    Some(value)
+}
+
+pub(crate) fn timezone_parser(mut rest: &[&str]) -> Result<Vec<Zone>, BotError> {
+    let mut zones = Vec::new();
+    while !rest.is_empty() {
+        let (zone, len) = parse_zone_prefix(rest)?;
+        if !zones.contains(&zone) {
+            zones.push(zone);
+        }
+        rest = &rest[len..];
+    }
+    Ok(zones)
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::TimeZone;
+    use chrono::{TimeZone, Weekday};
     use chrono_tz::America::Sao_Paulo;
     use chrono_tz::{CET, EET};
 
     use super::*;
 
+    fn zones(tzs: &[Tz]) -> Vec<Zone> {
+        tzs.iter().copied().map(Zone::from).collect()
+    }
+
     fn winter_now() -> DateTime<Utc> {
         Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap()
     }
@@ -104,10 +297,189 @@ mod tests {
         assert_eq!(converter.timezones, vec![CET, Sao_Paulo, EET]);
     }
 
+    #[test]
+    fn test_try_from_places() {
+        let converter = Converter::try_from("9am new york são paulo tokyo DE").unwrap();
+        assert_eq!(converter.base_time, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(
+            converter.timezones,
+            zones(&[
+                Tz::America__New_York,
+                Sao_Paulo,
+                Tz::Asia__Tokyo,
+                Tz::Europe__Berlin
+            ])
+        );
+    }
+
+    #[test]
+    fn test_try_from_meridiem() {
+        let converter = Converter::try_from("3:30 pm CET BRT").unwrap();
+        assert_eq!(converter.base_time, NaiveTime::from_hms_opt(15, 30, 0));
+        assert_eq!(converter.timezones, vec![CET, Sao_Paulo]);
+    }
+
+    #[test]
+    fn test_try_from_detached_single_letter_meridiem() {
+        assert!(matches!(
+            Converter::try_from("3 p UTC CET"),
+            Err(BotError::InvalidTimezone { .. })
+        ));
+        let converter = Converter::try_from("3p UTC CET").unwrap();
+        assert_eq!(converter.base_time, NaiveTime::from_hms_opt(15, 0, 0));
+    }
+
+    #[test]
+    fn test_try_from_date() {
+        let converter = Converter::try_from("2026-11-03 14:00 CET BRT").unwrap();
+        assert_eq!(
+            converter.base_date,
+            Some(DateSpec::Date(
+                NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()
+            ))
+        );
+        assert_eq!(converter.base_time, NaiveTime::from_hms_opt(14, 0, 0));
+        assert_eq!(converter.timezones, vec![CET, Sao_Paulo]);
+    }
+
+    #[test]
+    fn test_try_from_date_without_time() {
+        let result = Converter::try_from("tomorrow CET BRT");
+        assert_eq!(result.err(), Some(BotError::InvalidTime("CET".into())));
+    }
+
+    #[test]
+    fn test_try_from_invalid_date() {
+        let result = Converter::try_from("2026-02-30 12:00 UTC");
+        assert_eq!(
+            result.err(),
+            Some(BotError::InvalidDate("2026-02-30".into()))
+        );
+        let result = Converter::try_from("31/04 12:00 UTC");
+        assert_eq!(result.err(), Some(BotError::InvalidDate("31/04".into())));
+    }
+
+    #[test]
+    fn test_convert_time_with_date_across_dst_change() {
+        let converter = Converter::try_from("2026-11-03 14:00 CET BRT").unwrap();
+        let result = converter
+            .convert_time_between_timezones(summer_now())
+            .unwrap();
+        assert_eq!(result[0], "14:00 CET - 10:00 BRT");
+
+        let converter = Converter::try_from("14:00 CET BRT").unwrap();
+        let result = converter
+            .convert_time_between_timezones(summer_now())
+            .unwrap();
+        assert_eq!(result[0], "14:00 CET - 09:00 BRT");
+    }
+
+    #[test]
+    fn test_try_from_relative() {
+        let converter = Converter::try_from("in 45 minutes BRT CET").unwrap();
+        assert_eq!(converter.base_time, None);
+        assert_eq!(converter.shift, Some(TimeDelta::minutes(45)));
+        assert_eq!(converter.timezones, vec![Sao_Paulo, CET]);
+    }
+
+    #[test]
+    fn test_convert_relative_time() {
+        let converter = Converter::try_from("in 3h UTC BRT").unwrap();
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(
+            result,
+            vec!["15:00 UTC - 12:00 BRT", "12:00 BRT - 15:00 UTC"]
+        );
+    }
+
+    #[test]
+    fn test_convert_relative_time_overflow() {
+        let converter = Converter::try_from("in 2147483647d UTC").unwrap();
+        assert_eq!(
+            converter.convert_time_between_timezones(winter_now()),
+            Err(BotError::InvalidTime("in 2147483647d".into()))
+        );
+    }
+
+    #[test]
+    fn test_convert_past_the_last_representable_date() {
+        let converter = Converter::try_from("31/12/262142 23:00 UTC tokyo").unwrap();
+        assert_eq!(
+            converter.convert_time_between_timezones(winter_now()),
+            Err(BotError::InvalidTime("31/12/262142 23:00".into()))
+        );
+    }
+
+    #[test]
+    fn test_try_from_range() {
+        let converter = Converter::try_from("9h-18h CET BRT").unwrap();
+        assert_eq!(converter.base_time, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(converter.end_time, NaiveTime::from_hms_opt(18, 0, 0));
+        assert_eq!(converter.timezones, vec![CET, Sao_Paulo]);
+    }
+
+    #[test]
+    fn test_convert_range() {
+        let converter = Converter::try_from("9:00-17:30 CET BRT").unwrap();
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(result[0], "09:00-17:30 CET - 05:00-13:30 BRT");
+    }
+
+    #[test]
+    fn test_convert_range_crossing_midnight() {
+        let converter = Converter::try_from("18-23 BRT CET").unwrap();
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(
+            result[0],
+            "18:00-23:00 BRT - 22:00-03:00 CET (crosses midnight)"
+        );
+
+        let converter = Converter::try_from("22-6 UTC").unwrap();
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(result[0], "22:00-06:00 UTC (crosses midnight)");
+    }
+
+    #[test]
+    fn test_try_from_fixed_offset() {
+        let converter = Converter::try_from("12:00 UTC+5:30 +0545").unwrap();
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(result[0], "12:00 UTC+5:30 - 12:15 +0545");
+    }
+
+    #[test]
+    fn test_convert_time_day_shift() {
+        let converter = Converter::new(NaiveTime::from_hms_opt(23, 0, 0), zones(&[Sao_Paulo, CET]));
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(
+            result,
+            vec!["23:00 BRT - 03:00 CET (+1)", "23:00 CET - 19:00 BRT"]
+        );
+
+        let converter = Converter::new(NaiveTime::from_hms_opt(1, 0, 0), zones(&[CET, Sao_Paulo]));
+        let result = converter
+            .convert_time_between_timezones(winter_now())
+            .unwrap();
+        assert_eq!(result[0], "01:00 CET - 21:00 BRT (-1)");
+    }
+
     #[test]
     fn test_convert_time_winter() {
-        let converter =
-            Converter::new(NaiveTime::from_hms_opt(12, 0, 0), vec![CET, Sao_Paulo, EET]);
+        let converter = Converter::new(
+            NaiveTime::from_hms_opt(12, 0, 0),
+            zones(&[CET, Sao_Paulo, EET]),
+        );
         let result = converter
             .convert_time_between_timezones(winter_now())
             .unwrap();
@@ -124,8 +496,10 @@ mod tests {
 
     #[test]
     fn test_convert_time_summer() {
-        let converter =
-            Converter::new(NaiveTime::from_hms_opt(12, 0, 0), vec![CET, Sao_Paulo, EET]);
+        let converter = Converter::new(
+            NaiveTime::from_hms_opt(12, 0, 0),
+            zones(&[CET, Sao_Paulo, EET]),
+        );
         let result = converter
             .convert_time_between_timezones(summer_now())
             .unwrap();
@@ -142,8 +516,88 @@ mod tests {
 
     #[test]
     fn test_now_in_timezones() {
-        let converter = Converter::new(None, vec![Tz::UTC, Sao_Paulo]);
+        let converter = Converter::new(None, zones(&[Tz::UTC, Sao_Paulo]));
         let result: Vec<String> = converter.now_in_timezones(winter_now()).collect();
         assert_eq!(result, vec!["12:00 UTC", "09:00 BRT"]);
     }
+
+    #[test]
+    fn test_convert_epoch() {
+        let converter = Converter::try_from("1768478400 CET BRT").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(summer_now())
+                .unwrap()[0],
+            "2026-01-15 13:00 CET - 09:00 BRT"
+        );
+
+        let converter = Converter::try_from("1768519800000").unwrap();
+        assert_eq!(converter.timezones, zones(&[Tz::UTC]));
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "2026-01-15 23:30 UTC"
+        );
+    }
+
+    #[test]
+    fn test_convert_iso_datetime() {
+        let converter = Converter::try_from("2026-10-17T14:00+02:00 CET BRT").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "2026-10-17 14:00 +02:00 - 14:00 CET - 09:00 BRT"
+        );
+
+        let converter = Converter::try_from("2026-10-17T23:30:00Z tokyo").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "2026-10-17 23:30 UTC - 08:30 Asia/Tokyo (+1)"
+        );
+    }
+
+    #[test]
+    fn test_convert_keyword_time() {
+        let converter = Converter::try_from("tomorrow end of day CET BRT").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "18:00 CET - 14:00 BRT"
+        );
+        let converter = Converter::try_from("meia-noite BRT CET").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "00:00 BRT - 04:00 CET"
+        );
+    }
+
+    #[test]
+    fn test_convert_weekday_anchored() {
+        // 2026-01-15 is a Thursday.
+        let converter = Converter::try_from("Monday 10:00 PST CET").unwrap();
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "Mon 10:00 PST - Mon 19:00 CET"
+        );
+        let converter = Converter::try_from("next thursday 8pm PST tokyo").unwrap();
+        assert_eq!(
+            converter.base_date,
+            Some(DateSpec::NextWeekday(Weekday::Thu))
+        );
+        assert_eq!(
+            converter
+                .convert_time_between_timezones(winter_now())
+                .unwrap()[0],
+            "Thu 20:00 PST - Fri 13:00 Asia/Tokyo"
+        );
+    }
 }
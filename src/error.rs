@@ -1,13 +1,25 @@
 use chrono::NaiveTime;
-use chrono_tz::{ParseError, Tz};
+use chrono_tz::Tz;
+use itertools::Itertools;
 use thiserror::Error;
 
+use crate::zone::Zone;
+
 #[derive(Debug, Error, PartialEq)]
 pub enum BotError {
-    #[error("Invalid timezone: {0}")]
-    InvalidTimezone(#[from] ParseError),
+    #[error("Invalid timezone: {input}")]
+    InvalidTimezone {
+        input: String,
+        suggestions: Vec<String>,
+    },
+    #[error("{input} is ambiguous, it could mean {}", .candidates.iter().join(", "))]
+    AmbiguousTimezone { input: String, candidates: Vec<Tz> },
     #[error("Invalid time: {0}")]
     InvalidTime(String),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
     #[error("{time} does not exist in {tz} on that date (daylight saving transition)")]
-    NonexistentTime { time: NaiveTime, tz: Tz },
+    NonexistentTime { time: NaiveTime, tz: Zone },
+    #[error("Could not save your settings: {0}")]
+    Storage(String),
 }
@@ -0,0 +1,384 @@
+use chrono_tz::Tz;
+
+/// Longest place name, in words, that `lookup` is asked to match.
+pub const MAX_NAME_WORDS: usize = 4;
+
+const CITIES: &[(&str, Tz)] = &[
+    // Europe
+    ("amsterdam", Tz::Europe__Amsterdam),
+    ("athens", Tz::Europe__Athens),
+    ("barcelona", Tz::Europe__Madrid),
+    ("belgrade", Tz::Europe__Belgrade),
+    ("berlin", Tz::Europe__Berlin),
+    ("brussels", Tz::Europe__Brussels),
+    ("bucharest", Tz::Europe__Bucharest),
+    ("budapest", Tz::Europe__Budapest),
+    ("copenhagen", Tz::Europe__Copenhagen),
+    ("dublin", Tz::Europe__Dublin),
+    ("edinburgh", Tz::Europe__London),
+    ("frankfurt", Tz::Europe__Berlin),
+    ("geneva", Tz::Europe__Zurich),
+    ("hamburg", Tz::Europe__Berlin),
+    ("helsinki", Tz::Europe__Helsinki),
+    ("istanbul", Tz::Europe__Istanbul),
+    ("kyiv", Tz::Europe__Kyiv),
+    ("kiev", Tz::Europe__Kyiv),
+    ("lisbon", Tz::Europe__Lisbon),
+    ("lisboa", Tz::Europe__Lisbon),
+    ("london", Tz::Europe__London),
+    ("lyon", Tz::Europe__Paris),
+    ("madrid", Tz::Europe__Madrid),
+    ("manchester", Tz::Europe__London),
+    ("milan", Tz::Europe__Rome),
+    ("moscow", Tz::Europe__Moscow),
+    ("munich", Tz::Europe__Berlin),
+    ("oslo", Tz::Europe__Oslo),
+    ("paris", Tz::Europe__Paris),
+    ("porto", Tz::Europe__Lisbon),
+    ("prague", Tz::Europe__Prague),
+    ("riga", Tz::Europe__Riga),
+    ("rome", Tz::Europe__Rome),
+    ("rotterdam", Tz::Europe__Amsterdam),
+    ("sofia", Tz::Europe__Sofia),
+    ("stockholm", Tz::Europe__Stockholm),
+    ("tallinn", Tz::Europe__Tallinn),
+    ("valencia", Tz::Europe__Madrid),
+    ("vienna", Tz::Europe__Vienna),
+    ("vilnius", Tz::Europe__Vilnius),
+    ("warsaw", Tz::Europe__Warsaw),
+    ("zagreb", Tz::Europe__Zagreb),
+    ("zurich", Tz::Europe__Zurich),
+    // Americas
+    ("anchorage", Tz::America__Anchorage),
+    ("atlanta", Tz::America__New_York),
+    ("austin", Tz::America__Chicago),
+    ("belo horizonte", Tz::America__Sao_Paulo),
+    ("bogota", Tz::America__Bogota),
+    ("boston", Tz::America__New_York),
+    ("brasilia", Tz::America__Sao_Paulo),
+    ("buenos aires", Tz::America__Argentina__Buenos_Aires),
+    ("calgary", Tz::America__Edmonton),
+    ("caracas", Tz::America__Caracas),
+    ("chicago", Tz::America__Chicago),
+    ("curitiba", Tz::America__Sao_Paulo),
+    ("dallas", Tz::America__Chicago),
+    ("denver", Tz::America__Denver),
+    ("detroit", Tz::America__Detroit),
+    ("fortaleza", Tz::America__Fortaleza),
+    ("guadalajara", Tz::America__Mexico_City),
+    ("havana", Tz::America__Havana),
+    ("honolulu", Tz::Pacific__Honolulu),
+    ("houston", Tz::America__Chicago),
+    ("la", Tz::America__Los_Angeles),
+    ("las vegas", Tz::America__Los_Angeles),
+    ("lima", Tz::America__Lima),
+    ("los angeles", Tz::America__Los_Angeles),
+    ("manaus", Tz::America__Manaus),
+    ("mexico city", Tz::America__Mexico_City),
+    ("miami", Tz::America__New_York),
+    ("montevideo", Tz::America__Montevideo),
+    ("montreal", Tz::America__Toronto),
+    ("new york", Tz::America__New_York),
+    ("new york city", Tz::America__New_York),
+    ("nyc", Tz::America__New_York),
+    ("panama", Tz::America__Panama),
+    ("philadelphia", Tz::America__New_York),
+    ("phoenix", Tz::America__Phoenix),
+    ("porto alegre", Tz::America__Sao_Paulo),
+    ("recife", Tz::America__Recife),
+    ("rio", Tz::America__Sao_Paulo),
+    ("rio de janeiro", Tz::America__Sao_Paulo),
+    ("salvador", Tz::America__Bahia),
+    ("san francisco", Tz::America__Los_Angeles),
+    ("santiago", Tz::America__Santiago),
+    ("sao paulo", Tz::America__Sao_Paulo),
+    ("seattle", Tz::America__Los_Angeles),
+    ("sf", Tz::America__Los_Angeles),
+    ("toronto", Tz::America__Toronto),
+    ("vancouver", Tz::America__Vancouver),
+    ("washington", Tz::America__New_York),
+    ("winnipeg", Tz::America__Winnipeg),
+    // Asia and Oceania
+    ("abu dhabi", Tz::Asia__Dubai),
+    ("auckland", Tz::Pacific__Auckland),
+    ("bangalore", Tz::Asia__Kolkata),
+    ("bangkok", Tz::Asia__Bangkok),
+    ("beijing", Tz::Asia__Shanghai),
+    ("bengaluru", Tz::Asia__Kolkata),
+    ("brisbane", Tz::Australia__Brisbane),
+    ("chennai", Tz::Asia__Kolkata),
+    ("delhi", Tz::Asia__Kolkata),
+    ("dhaka", Tz::Asia__Dhaka),
+    ("doha", Tz::Asia__Qatar),
+    ("dubai", Tz::Asia__Dubai),
+    ("hanoi", Tz::Asia__Bangkok),
+    ("ho chi minh city", Tz::Asia__Ho_Chi_Minh),
+    ("hong kong", Tz::Asia__Hong_Kong),
+    ("hyderabad", Tz::Asia__Kolkata),
+    ("jakarta", Tz::Asia__Jakarta),
+    ("jerusalem", Tz::Asia__Jerusalem),
+    ("karachi", Tz::Asia__Karachi),
+    ("kathmandu", Tz::Asia__Kathmandu),
+    ("kolkata", Tz::Asia__Kolkata),
+    ("kuala lumpur", Tz::Asia__Kuala_Lumpur),
+    ("manila", Tz::Asia__Manila),
+    ("melbourne", Tz::Australia__Melbourne),
+    ("mumbai", Tz::Asia__Kolkata),
+    ("new delhi", Tz::Asia__Kolkata),
+    ("osaka", Tz::Asia__Tokyo),
+    ("perth", Tz::Australia__Perth),
+    ("riyadh", Tz::Asia__Riyadh),
+    ("saigon", Tz::Asia__Ho_Chi_Minh),
+    ("seoul", Tz::Asia__Seoul),
+    ("shanghai", Tz::Asia__Shanghai),
+    ("shenzhen", Tz::Asia__Shanghai),
+    ("singapore", Tz::Asia__Singapore),
+    ("sydney", Tz::Australia__Sydney),
+    ("taipei", Tz::Asia__Taipei),
+    ("tehran", Tz::Asia__Tehran),
+    ("tel aviv", Tz::Asia__Jerusalem),
+    ("tokyo", Tz::Asia__Tokyo),
+    ("wellington", Tz::Pacific__Auckland),
+    // Africa
+    ("accra", Tz::Africa__Accra),
+    ("addis ababa", Tz::Africa__Addis_Ababa),
+    ("cairo", Tz::Africa__Cairo),
+    ("cape town", Tz::Africa__Johannesburg),
+    ("casablanca", Tz::Africa__Casablanca),
+    ("johannesburg", Tz::Africa__Johannesburg),
+    ("lagos", Tz::Africa__Lagos),
+    ("luanda", Tz::Africa__Luanda),
+    ("maputo", Tz::Africa__Maputo),
+    ("nairobi", Tz::Africa__Nairobi),
+    ("tunis", Tz::Africa__Tunis),
+];
+
+/// ISO 3166 country codes and common country names. Countries spanning
+/// several zones map to the zone of their capital or largest city.
+const COUNTRIES: &[(&str, Tz)] = &[
+    ("ae", Tz::Asia__Dubai),
+    ("ar", Tz::America__Argentina__Buenos_Aires),
+    ("argentina", Tz::America__Argentina__Buenos_Aires),
+    ("at", Tz::Europe__Vienna),
+    ("austria", Tz::Europe__Vienna),
+    ("au", Tz::Australia__Sydney),
+    ("australia", Tz::Australia__Sydney),
+    ("be", Tz::Europe__Brussels),
+    ("belgium", Tz::Europe__Brussels),
+    ("bg", Tz::Europe__Sofia),
+    ("bo", Tz::America__La_Paz),
+    ("br", Tz::America__Sao_Paulo),
+    ("brasil", Tz::America__Sao_Paulo),
+    ("brazil", Tz::America__Sao_Paulo),
+    ("ca", Tz::America__Toronto),
+    ("canada", Tz::America__Toronto),
+    ("ch", Tz::Europe__Zurich),
+    ("switzerland", Tz::Europe__Zurich),
+    ("cl", Tz::America__Santiago),
+    ("chile", Tz::America__Santiago),
+    ("cn", Tz::Asia__Shanghai),
+    ("china", Tz::Asia__Shanghai),
+    ("co", Tz::America__Bogota),
+    ("colombia", Tz::America__Bogota),
+    ("cr", Tz::America__Costa_Rica),
+    ("cu", Tz::America__Havana),
+    ("cz", Tz::Europe__Prague),
+    ("de", Tz::Europe__Berlin),
+    ("germany", Tz::Europe__Berlin),
+    ("alemanha", Tz::Europe__Berlin),
+    ("dk", Tz::Europe__Copenhagen),
+    ("denmark", Tz::Europe__Copenhagen),
+    ("ec", Tz::America__Guayaquil),
+    ("ee", Tz::Europe__Tallinn),
+    ("eg", Tz::Africa__Cairo),
+    ("egypt", Tz::Africa__Cairo),
+    ("es", Tz::Europe__Madrid),
+    ("spain", Tz::Europe__Madrid),
+    ("espanha", Tz::Europe__Madrid),
+    ("fi", Tz::Europe__Helsinki),
+    ("finland", Tz::Europe__Helsinki),
+    ("fr", Tz::Europe__Paris),
+    ("france", Tz::Europe__Paris),
+    ("franca", Tz::Europe__Paris),
+    ("gb", Tz::Europe__London),
+    ("uk", Tz::Europe__London),
+    ("united kingdom", Tz::Europe__London),
+    ("england", Tz::Europe__London),
+    ("gr", Tz::Europe__Athens),
+    ("greece", Tz::Europe__Athens),
+    ("hk", Tz::Asia__Hong_Kong),
+    ("hr", Tz::Europe__Zagreb),
+    ("hu", Tz::Europe__Budapest),
+    ("id", Tz::Asia__Jakarta),
+    ("indonesia", Tz::Asia__Jakarta),
+    ("ie", Tz::Europe__Dublin),
+    ("ireland", Tz::Europe__Dublin),
+    ("irlanda", Tz::Europe__Dublin),
+    ("il", Tz::Asia__Jerusalem),
+    ("israel", Tz::Asia__Jerusalem),
+    ("in", Tz::Asia__Kolkata),
+    ("india", Tz::Asia__Kolkata),
+    ("ir", Tz::Asia__Tehran),
+    ("is", Tz::Atlantic__Reykjavik),
+    ("it", Tz::Europe__Rome),
+    ("italy", Tz::Europe__Rome),
+    ("italia", Tz::Europe__Rome),
+    ("jp", Tz::Asia__Tokyo),
+    ("japan", Tz::Asia__Tokyo),
+    ("japao", Tz::Asia__Tokyo),
+    ("ke", Tz::Africa__Nairobi),
+    ("kr", Tz::Asia__Seoul),
+    ("south korea", Tz::Asia__Seoul),
+    ("lt", Tz::Europe__Vilnius),
+    ("lu", Tz::Europe__Luxembourg),
+    ("lv", Tz::Europe__Riga),
+    ("ma", Tz::Africa__Casablanca),
+    ("mx", Tz::America__Mexico_City),
+    ("mexico", Tz::America__Mexico_City),
+    ("my", Tz::Asia__Kuala_Lumpur),
+    ("ng", Tz::Africa__Lagos),
+    ("nigeria", Tz::Africa__Lagos),
+    ("nl", Tz::Europe__Amsterdam),
+    ("netherlands", Tz::Europe__Amsterdam),
+    ("holanda", Tz::Europe__Amsterdam),
+    ("no", Tz::Europe__Oslo),
+    ("norway", Tz::Europe__Oslo),
+    ("np", Tz::Asia__Kathmandu),
+    ("nz", Tz::Pacific__Auckland),
+    ("new zealand", Tz::Pacific__Auckland),
+    ("pe", Tz::America__Lima),
+    ("peru", Tz::America__Lima),
+    ("ph", Tz::Asia__Manila),
+    ("philippines", Tz::Asia__Manila),
+    ("pk", Tz::Asia__Karachi),
+    ("pakistan", Tz::Asia__Karachi),
+    ("pl", Tz::Europe__Warsaw),
+    ("poland", Tz::Europe__Warsaw),
+    ("pt", Tz::Europe__Lisbon),
+    ("portugal", Tz::Europe__Lisbon),
+    ("py", Tz::America__Asuncion),
+    ("ro", Tz::Europe__Bucharest),
+    ("romania", Tz::Europe__Bucharest),
+    ("romenia", Tz::Europe__Bucharest),
+    ("rs", Tz::Europe__Belgrade),
+    ("ru", Tz::Europe__Moscow),
+    ("russia", Tz::Europe__Moscow),
+    ("sa", Tz::Asia__Riyadh),
+    ("se", Tz::Europe__Stockholm),
+    ("sweden", Tz::Europe__Stockholm),
+    ("sg", Tz::Asia__Singapore),
+    ("th", Tz::Asia__Bangkok),
+    ("thailand", Tz::Asia__Bangkok),
+    ("tr", Tz::Europe__Istanbul),
+    ("turkey", Tz::Europe__Istanbul),
+    ("tw", Tz::Asia__Taipei),
+    ("taiwan", Tz::Asia__Taipei),
+    ("ua", Tz::Europe__Kyiv),
+    ("ukraine", Tz::Europe__Kyiv),
+    ("us", Tz::America__New_York),
+    ("usa", Tz::America__New_York),
+    ("united states", Tz::America__New_York),
+    ("eua", Tz::America__New_York),
+    ("uy", Tz::America__Montevideo),
+    ("uruguay", Tz::America__Montevideo),
+    ("ve", Tz::America__Caracas),
+    ("venezuela", Tz::America__Caracas),
+    ("vn", Tz::Asia__Ho_Chi_Minh),
+    ("vietnam", Tz::Asia__Ho_Chi_Minh),
+    ("za", Tz::Africa__Johannesburg),
+    ("south africa", Tz::Africa__Johannesburg),
+];
+
+/// Lowercases `text`, strips common Latin diacritics and collapses
+/// separators so "São_Paulo" and "sao paulo" compare equal.
+pub fn normalize(text: &str) -> String {
+    let folded: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'ç' => 'c',
+            'è' | 'é' | 'ê' | 'ë' => 'e',
+            'ì' | 'í' | 'î' | 'ï' => 'i',
+            'ñ' => 'n',
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' => 'o',
+            'ù' | 'ú' | 'û' | 'ü' => 'u',
+            'ý' | 'ÿ' => 'y',
+            '_' | '-' => ' ',
+            c => c,
+        })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Looks up a city, country code or country name in the embedded dataset.
+pub fn lookup(name: &str) -> Option<Tz> {
+    let key = normalize(name);
+    CITIES
+        .iter()
+        .chain(COUNTRIES)
+        .find(|(place, _)| *place == key)
+        .map(|(_, tz)| *tz)
+}
+
+/// Whether `word` is a country code written in lowercase, such as "no",
+/// "is" or "it", which in free-form messages are everyday words.
+pub fn is_lowercase_country_code(word: &str) -> bool {
+    word.len() == 2
+        && word.chars().any(|c| c.is_lowercase())
+        && COUNTRIES.iter().any(|(code, _)| *code == normalize(word))
+}
+
+/// Every place in the dataset, with its name already normalized.
+pub fn places() -> impl Iterator<Item = (&'static str, Tz)> {
+    CITIES.iter().chain(COUNTRIES).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_city() {
+        assert_eq!(lookup("tokyo"), Some(Tz::Asia__Tokyo));
+        assert_eq!(lookup("New York"), Some(Tz::America__New_York));
+        assert_eq!(lookup("são paulo"), Some(Tz::America__Sao_Paulo));
+        assert_eq!(lookup("Sao_Paulo"), Some(Tz::America__Sao_Paulo));
+    }
+
+    #[test]
+    fn test_lookup_country() {
+        assert_eq!(lookup("DE"), Some(Tz::Europe__Berlin));
+        assert_eq!(lookup("brasil"), Some(Tz::America__Sao_Paulo));
+        assert_eq!(lookup("new zealand"), Some(Tz::Pacific__Auckland));
+    }
+
+    #[test]
+    fn test_lookup_code_ignores_case() {
+        assert_eq!(lookup("IS"), Some(Tz::Atlantic__Reykjavik));
+        assert_eq!(lookup("br"), Some(Tz::America__Sao_Paulo));
+        assert_eq!(lookup("nl"), Some(Tz::Europe__Amsterdam));
+        assert_eq!(lookup("la"), Some(Tz::America__Los_Angeles));
+        assert_eq!(lookup("sf"), Some(Tz::America__Los_Angeles));
+    }
+
+    #[test]
+    fn test_is_lowercase_country_code() {
+        for word in ["no", "is", "it", "us", "De"] {
+            assert!(is_lowercase_country_code(word), "{word}");
+        }
+        for word in ["NO", "la", "sf", "at6", "tokyo"] {
+            assert!(!is_lowercase_country_code(word), "{word}");
+        }
+    }
+
+    #[test]
+    fn test_lookup_unknown() {
+        assert_eq!(lookup("atlantis"), None);
+    }
+
+    #[test]
+    fn test_names_fit_word_limit() {
+        assert!(places().all(|(name, _)| name.split(' ').count() <= MAX_NAME_WORDS));
+    }
+}
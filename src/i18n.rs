@@ -0,0 +1,372 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::error::BotError;
+
+/// A language the bot can reply in.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Self; 3] = [Self::English, Self::Portuguese, Self::Spanish];
+
+    /// Picks a language from a Telegram `language_code` or a /lang argument,
+    /// e.g. "pt-br" or "es".
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == primary)
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Portuguese => "pt",
+            Self::Spanish => "es",
+        }
+    }
+
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Self::English => &ENGLISH,
+            Self::Portuguese => &PORTUGUESE,
+            Self::Spanish => &SPANISH,
+        }
+    }
+}
+
+/// Every sentence the bot replies with, in one language. Placeholders such
+/// as `{home}` are filled in with `fill`.
+pub struct Messages {
+    pub welcome: &'static str,
+    pub invalid_command: &'static str,
+    pub commands_accepted: &'static str,
+    pub which_one: &'static str,
+    pub did_you_mean: &'static str,
+    pub follow_pattern: &'static str,
+    pub use_command: &'static str,
+    pub usage: &'static str,
+    pub no_time_to_convert: &'static str,
+    pub no_home_timezone: &'static str,
+    pub home_timezone: &'static str,
+    pub favourites: &'static str,
+    pub language_set: &'static str,
+    pub language_auto: &'static str,
+    pub scan_on: &'static str,
+    pub scan_off: &'static str,
+    pub time_format: &'static str,
+    pub chat_time_format: &'static str,
+    pub crosses_midnight: &'static str,
+    /// Abbreviated weekday names, Monday first.
+    pub weekdays: [&'static str; 7],
+    pub no_chat_timezones: &'static str,
+    pub chat_timezones: &'static str,
+    pub no_members: &'static str,
+    pub outside_hours: &'static str,
+    pub no_meeting_zones: &'static str,
+    pub meeting_overlap: &'static str,
+    pub meeting_least_bad: &'static str,
+    pub outside_working_hours: &'static str,
+    pub utc_offset: &'static str,
+    pub daylight_saving: &'static str,
+    pub yes: &'static str,
+    pub no: &'static str,
+    pub next_change: &'static str,
+    pub previous_change: &'static str,
+    pub no_offset_change: &'static str,
+    pub clocks_forward: &'static str,
+    pub clocks_back: &'static str,
+    pub until: &'static str,
+    pub was_ago: &'static str,
+    pub since: &'static str,
+    pub is_away: &'static str,
+    /// Singular and plural.
+    pub days: [&'static str; 2],
+    pub hours: [&'static str; 2],
+    pub minutes: [&'static str; 2],
+    pub same_time_as: &'static str,
+    pub ahead_of: &'static str,
+    pub behind: &'static str,
+    pub gap_same: &'static str,
+    pub gap_ahead: &'static str,
+    pub gap_behind: &'static str,
+    pub gap_from: &'static str,
+    pub invalid_timezone: &'static str,
+    pub ambiguous_timezone: &'static str,
+    pub invalid_time: &'static str,
+    pub invalid_date: &'static str,
+    pub nonexistent_time: &'static str,
+    pub storage: &'static str,
+}
+
+pub const ENGLISH: Messages = Messages {
+    welcome: "Welcome!",
+    invalid_command: "Invalid command.",
+    commands_accepted: "Commands accepted:",
+    which_one: "{error}. Which one did you mean?",
+    did_you_mean: "Did you mean {suggestions}?",
+    follow_pattern: "Please follow the pattern below",
+    use_command: "Use {command}",
+    usage: "Usage: {command}",
+    no_time_to_convert: "No time to convert",
+    no_home_timezone: "You have no home timezone yet.",
+    home_timezone: "Home timezone: {home}",
+    favourites: "Favourites: {favourites}",
+    language_set: "Replies will be in English.",
+    language_auto: "Replies will follow your Telegram language.",
+    scan_on: "Looking for times in every message of this chat.",
+    scan_off: "Only converting messages that are entirely a time.\n\nUse /scan on to look for times in every message.",
+    time_format: "Time format: {format}",
+    chat_time_format: "Time format for this chat: {format}",
+    crosses_midnight: "(crosses midnight)",
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    no_chat_timezones: "This chat has no timezones yet.",
+    chat_timezones: "Timezones for this chat: {zones}",
+    no_members: "No one has registered in this chat yet.",
+    outside_hours: "outside {hours}",
+    no_meeting_zones: "No timezones to plan a meeting for",
+    meeting_overlap: "Everyone is within working hours:",
+    meeting_least_bad: "Working hours do not overlap. Least bad option:",
+    outside_working_hours: "Outside working hours: {zones}",
+    utc_offset: "UTC offset",
+    daylight_saving: "Daylight saving",
+    yes: "yes",
+    no: "no",
+    next_change: "Next change",
+    previous_change: "Previous change",
+    no_offset_change: "none in two years",
+    clocks_forward: "{when}, clocks go forward {amount}",
+    clocks_back: "{when}, clocks go back {amount}",
+    until: "{duration} until {target}",
+    was_ago: "{target} was {duration} ago",
+    since: "{duration} since {target}",
+    is_away: "{target} is {duration} away",
+    days: ["day", "days"],
+    hours: ["hour", "hours"],
+    minutes: ["minute", "minutes"],
+    same_time_as: "{first} has the same time as {other}",
+    ahead_of: "{first} is {amount} ahead of {other}",
+    behind: "{first} is {amount} behind {other}",
+    gap_same: "same time",
+    gap_ahead: "{amount} ahead",
+    gap_behind: "{amount} behind",
+    gap_from: "{gap} from {date}",
+    invalid_timezone: "Invalid timezone: {input}",
+    ambiguous_timezone: "{input} is ambiguous, it could mean {candidates}",
+    invalid_time: "Invalid time: {input}",
+    invalid_date: "Invalid date: {input}",
+    nonexistent_time: "{time} does not exist in {tz} on that date (daylight saving transition)",
+    storage: "Could not save your settings: {reason}",
+};
+
+pub const PORTUGUESE: Messages = Messages {
+    welcome: "Bem-vindo!",
+    invalid_command: "Comando inválido.",
+    commands_accepted: "Comandos aceitos:",
+    which_one: "{error}. Qual você quis dizer?",
+    did_you_mean: "Você quis dizer {suggestions}?",
+    follow_pattern: "Siga o padrão abaixo",
+    use_command: "Use {command}",
+    usage: "Uso: {command}",
+    no_time_to_convert: "Nenhum horário para converter",
+    no_home_timezone: "Você ainda não tem um fuso horário principal.",
+    home_timezone: "Fuso horário principal: {home}",
+    favourites: "Favoritos: {favourites}",
+    language_set: "As respostas serão em português.",
+    language_auto: "As respostas seguirão o idioma do seu Telegram.",
+    scan_on: "Procurando horários em todas as mensagens deste chat.",
+    scan_off: "Convertendo apenas mensagens que são só um horário.\n\nUse /scan on para procurar horários em todas as mensagens.",
+    time_format: "Formato de hora: {format}",
+    chat_time_format: "Formato de hora deste chat: {format}",
+    crosses_midnight: "(passa da meia-noite)",
+    weekdays: ["seg", "ter", "qua", "qui", "sex", "sáb", "dom"],
+    no_chat_timezones: "Este chat ainda não tem fusos horários.",
+    chat_timezones: "Fusos horários deste chat: {zones}",
+    no_members: "Ninguém se registrou neste chat ainda.",
+    outside_hours: "fora de {hours}",
+    no_meeting_zones: "Nenhum fuso horário para planejar uma reunião",
+    meeting_overlap: "Todos estão em horário de trabalho:",
+    meeting_least_bad: "Os horários de trabalho não se sobrepõem. Opção menos ruim:",
+    outside_working_hours: "Fora do horário de trabalho: {zones}",
+    utc_offset: "Diferença para UTC",
+    daylight_saving: "Horário de verão",
+    yes: "sim",
+    no: "não",
+    next_change: "Próxima mudança",
+    previous_change: "Mudança anterior",
+    no_offset_change: "nenhuma em dois anos",
+    clocks_forward: "{when}, relógios adiantam {amount}",
+    clocks_back: "{when}, relógios atrasam {amount}",
+    until: "{duration} até {target}",
+    was_ago: "{target} foi há {duration}",
+    since: "{duration} desde {target}",
+    is_away: "{target} ainda está a {duration}",
+    days: ["dia", "dias"],
+    hours: ["hora", "horas"],
+    minutes: ["minuto", "minutos"],
+    same_time_as: "{first} tem o mesmo horário que {other}",
+    ahead_of: "{first} está {amount} à frente de {other}",
+    behind: "{first} está {amount} atrás de {other}",
+    gap_same: "mesmo horário",
+    gap_ahead: "{amount} à frente",
+    gap_behind: "{amount} atrás",
+    gap_from: "{gap} a partir de {date}",
+    invalid_timezone: "Fuso horário inválido: {input}",
+    ambiguous_timezone: "{input} é ambíguo, pode ser {candidates}",
+    invalid_time: "Horário inválido: {input}",
+    invalid_date: "Data inválida: {input}",
+    nonexistent_time: "{time} não existe em {tz} nessa data (mudança de horário de verão)",
+    storage: "Não foi possível salvar suas configurações: {reason}",
+};
+
+pub const SPANISH: Messages = Messages {
+    welcome: "¡Bienvenido!",
+    invalid_command: "Comando inválido.",
+    commands_accepted: "Comandos aceptados:",
+    which_one: "{error}. ¿Cuál quisiste decir?",
+    did_you_mean: "¿Quisiste decir {suggestions}?",
+    follow_pattern: "Sigue el patrón de abajo",
+    use_command: "Usa {command}",
+    usage: "Uso: {command}",
+    no_time_to_convert: "No hay hora para convertir",
+    no_home_timezone: "Todavía no tienes una zona horaria principal.",
+    home_timezone: "Zona horaria principal: {home}",
+    favourites: "Favoritas: {favourites}",
+    language_set: "Las respuestas serán en español.",
+    language_auto: "Las respuestas seguirán el idioma de tu Telegram.",
+    scan_on: "Buscando horas en todos los mensajes de este chat.",
+    scan_off: "Solo se convierten los mensajes que son únicamente una hora.\n\nUsa /scan on para buscar horas en todos los mensajes.",
+    time_format: "Formato de hora: {format}",
+    chat_time_format: "Formato de hora de este chat: {format}",
+    crosses_midnight: "(pasa de medianoche)",
+    weekdays: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    no_chat_timezones: "Este chat todavía no tiene zonas horarias.",
+    chat_timezones: "Zonas horarias de este chat: {zones}",
+    no_members: "Nadie se ha registrado en este chat todavía.",
+    outside_hours: "fuera de {hours}",
+    no_meeting_zones: "No hay zonas horarias para planear una reunión",
+    meeting_overlap: "Todos están en horario laboral:",
+    meeting_least_bad: "Los horarios laborales no se superponen. Opción menos mala:",
+    outside_working_hours: "Fuera del horario laboral: {zones}",
+    utc_offset: "Diferencia con UTC",
+    daylight_saving: "Horario de verano",
+    yes: "sí",
+    no: "no",
+    next_change: "Próximo cambio",
+    previous_change: "Cambio anterior",
+    no_offset_change: "ninguno en dos años",
+    clocks_forward: "{when}, los relojes se adelantan {amount}",
+    clocks_back: "{when}, los relojes se atrasan {amount}",
+    until: "{duration} hasta {target}",
+    was_ago: "{target} fue hace {duration}",
+    since: "{duration} desde {target}",
+    is_away: "{target} todavía está a {duration}",
+    days: ["día", "días"],
+    hours: ["hora", "horas"],
+    minutes: ["minuto", "minutos"],
+    same_time_as: "{first} tiene la misma hora que {other}",
+    ahead_of: "{first} va {amount} por delante de {other}",
+    behind: "{first} va {amount} por detrás de {other}",
+    gap_same: "misma hora",
+    gap_ahead: "{amount} por delante",
+    gap_behind: "{amount} por detrás",
+    gap_from: "{gap} desde el {date}",
+    invalid_timezone: "Zona horaria inválida: {input}",
+    ambiguous_timezone: "{input} es ambiguo, puede ser {candidates}",
+    invalid_time: "Hora inválida: {input}",
+    invalid_date: "Fecha inválida: {input}",
+    nonexistent_time: "{time} no existe en {tz} en esa fecha (cambio de horario de verano)",
+    storage: "No se pudo guardar tu configuración: {reason}",
+};
+
+/// Replaces each `{name}` in `template` with its value.
+pub fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+impl Messages {
+    /// `error` in this language. `BotError`'s own `Display` stays English.
+    pub fn error(&self, error: &BotError) -> String {
+        match error {
+            BotError::InvalidTimezone { input, .. } => {
+                fill(self.invalid_timezone, &[("input", input)])
+            }
+            BotError::AmbiguousTimezone { input, candidates } => fill(
+                self.ambiguous_timezone,
+                &[
+                    ("input", input),
+                    ("candidates", &candidates.iter().join(", ")),
+                ],
+            ),
+            BotError::InvalidTime(input) => fill(self.invalid_time, &[("input", input)]),
+            BotError::InvalidDate(input) => fill(self.invalid_date, &[("input", input)]),
+            BotError::NonexistentTime { time, tz } => {
+                fill(self.nonexistent_time, &[("time", time), ("tz", tz)])
+            }
+            BotError::Storage(reason) => fill(self.storage, &[("reason", reason)]),
+        }
+    }
+
+    /// E.g. "1 day" or "3 hours".
+    pub fn amount(&self, amount: i64, unit: &[&str; 2]) -> String {
+        let unit = if amount == 1 { unit[0] } else { unit[1] };
+        format!("{amount} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+    use chrono_tz::Tz;
+
+    use super::*;
+
+    #[test]
+    fn test_language_from_code() {
+        assert_eq!(Language::from_code("pt-br"), Some(Language::Portuguese));
+        assert_eq!(Language::from_code("ES"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("en_US"), Some(Language::English));
+        assert_eq!(Language::from_code("de"), None);
+    }
+
+    #[test]
+    fn test_english_errors_match_display() {
+        let errors = [
+            BotError::InvalidTimezone {
+                input: "Nowhere".into(),
+                suggestions: Vec::new(),
+            },
+            BotError::AmbiguousTimezone {
+                input: "IST".into(),
+                candidates: vec![Tz::Europe__Dublin, Tz::Asia__Kolkata],
+            },
+            BotError::InvalidTime("25:00".into()),
+            BotError::InvalidDate("31/02".into()),
+            BotError::NonexistentTime {
+                time: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+                tz: Tz::CET.into(),
+            },
+            BotError::Storage("disk full".into()),
+        ];
+        for error in errors {
+            assert_eq!(ENGLISH.error(&error), error.to_string());
+        }
+    }
+
+    #[test]
+    fn test_localized_error() {
+        let error = BotError::InvalidTime("25:00".into());
+        assert_eq!(PORTUGUESE.error(&error), "Horário inválido: 25:00");
+        assert_eq!(SPANISH.error(&error), "Hora inválida: 25:00");
+    }
+}
@@ -2,5 +2,12 @@ pub mod api;
 pub mod command;
 pub mod converter;
 pub mod error;
+pub mod gazetteer;
+pub mod i18n;
+pub mod meeting;
+pub mod scanner;
+pub mod storage;
+pub mod suggest;
 pub mod telegram;
 pub mod time;
+pub mod zone;
@@ -1,11 +1,37 @@
+use std::sync::Arc;
+
+use actix_web::web::Data;
 use actix_web::{App, HttpServer};
 
-use chronosbot::api::{receive_message, welcome};
+use chronosbot::api::{BotInfo, receive_message, welcome};
+use chronosbot::storage::{JsonStorage, Storage};
+use chronosbot::telegram::BotApi;
+
+const DEFAULT_STORAGE_PATH: &str = "chronosbot.json";
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| App::new().service(welcome).service(receive_message))
-        .bind(("0.0.0.0", 3000))?
-        .run()
-        .await
+    let path =
+        std::env::var("CHRONOSBOT_STORAGE").unwrap_or_else(|_| DEFAULT_STORAGE_PATH.to_string());
+    let storage: Arc<dyn Storage> = Arc::new(JsonStorage::open(path)?);
+    let storage = Data::from(storage);
+    let api = std::env::var("CHRONOSBOT_TOKEN").ok().map(BotApi::new);
+    // Without a username, commands addressed to other bots cannot be told
+    // apart, so ask Telegram when it is not configured.
+    let username = std::env::var("CHRONOSBOT_USERNAME").ok().or_else(|| {
+        let me = api.as_ref()?.get_me().ok()??;
+        me.username
+    });
+    let bot = Data::new(BotInfo { username, api });
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(storage.clone())
+            .app_data(bot.clone())
+            .service(welcome)
+            .service(receive_message)
+    })
+    .bind(("0.0.0.0", 3000))?
+    .run()
+    .await
 }
@@ -0,0 +1,278 @@
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use itertools::Itertools;
+
+use crate::converter::{end_of_range, take_prefix, timezone_parser};
+use crate::error::BotError;
+use crate::i18n::{Messages, fill};
+use crate::time::{
+    DateSpec, TimeFormat, WORKING_HOURS, format_time_range_with_timezone,
+    format_time_with_timezone, format_timezone, parse_date_prefix, parse_time_range_prefix,
+    time_with_timezone,
+};
+use crate::zone::Zone;
+
+const UTC: Zone = Zone::Named(Tz::UTC);
+
+/// Length of the meeting suggested when working hours do not overlap.
+const MEETING_LENGTH: TimeDelta = TimeDelta::hours(1);
+
+/// Start and end of someone's working day, in UTC.
+type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+/// Looks for a time when everyone in `timezones` is within working hours.
+pub struct MeetingPlanner {
+    pub date: Option<DateSpec>,
+    pub working_hours: (NaiveTime, NaiveTime),
+    pub timezones: Vec<Zone>,
+    pub format: TimeFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MeetingWindow {
+    /// Everyone is within working hours from `start` until `end`.
+    Overlap {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// The meeting start that keeps people the least time outside working
+    /// hours, and the zones that would still be outside them.
+    LeastBad {
+        start: DateTime<Utc>,
+        outside: Vec<Zone>,
+    },
+}
+
+impl MeetingPlanner {
+    pub fn new(timezones: Vec<Zone>) -> Self {
+        let (start, end) = WORKING_HOURS;
+        Self {
+            date: None,
+            working_hours: (hour(start), hour(end)),
+            timezones,
+            format: TimeFormat::default(),
+        }
+    }
+
+    /// Working hours of each zone on the requested date, or today in the
+    /// first zone.
+    fn working_intervals(&self, now: DateTime<Utc>) -> Result<Vec<Interval>, BotError> {
+        let Some(first) = self.timezones.first() else {
+            return Ok(Vec::new());
+        };
+        let today = now.with_timezone(first).date_naive();
+        let date = match &self.date {
+            Some(date) => date.resolve(today)?,
+            None => today,
+        };
+        let (start_time, end_time) = &self.working_hours;
+        self.timezones
+            .iter()
+            .map(|tz| {
+                let start = time_with_timezone(start_time, tz, date)?;
+                let end = end_of_range(end_time, &start)?;
+                Ok((start.to_utc(), end.to_utc()))
+            })
+            .collect()
+    }
+
+    pub fn find_window(&self, now: DateTime<Utc>) -> Result<Option<MeetingWindow>, BotError> {
+        let intervals = self.working_intervals(now)?;
+        let (Some(start), Some(end)) = (
+            intervals.iter().map(|(start, _)| *start).max(),
+            intervals.iter().map(|(_, end)| *end).min(),
+        ) else {
+            return Ok(None);
+        };
+        if start < end {
+            return Ok(Some(MeetingWindow::Overlap { start, end }));
+        }
+
+        // How long a meeting starting at `start` runs outside each zone's
+        // working hours. The total only changes slope at interval bounds, so
+        // one of them is the best start.
+        let time_outside = |start: DateTime<Utc>, (work_start, work_end): Interval| {
+            (work_start - start).max(TimeDelta::zero())
+                + (start + MEETING_LENGTH - work_end).max(TimeDelta::zero())
+        };
+        let start = intervals
+            .iter()
+            .flat_map(|(start, end)| [*start, *end - MEETING_LENGTH])
+            .sorted()
+            .min_by_key(|start| {
+                intervals
+                    .iter()
+                    .map(|interval| time_outside(*start, *interval))
+                    .sum::<TimeDelta>()
+            })
+            .unwrap_or(start);
+        let outside = self
+            .timezones
+            .iter()
+            .zip(&intervals)
+            .filter(|(_, interval)| time_outside(start, **interval) > TimeDelta::zero())
+            .map(|(tz, _)| tz.clone())
+            .collect();
+        Ok(Some(MeetingWindow::LeastBad { start, outside }))
+    }
+
+    /// The zones shown in the answer: UTC, which the windows are found in,
+    /// followed by the requested ones.
+    fn shown_zones(&self) -> impl Iterator<Item = &Zone> {
+        std::iter::once(&UTC).chain(self.timezones.iter().filter(|tz| **tz != UTC))
+    }
+
+    pub fn describe(&self, now: DateTime<Utc>, t: &Messages) -> Result<String, BotError> {
+        let ret = match self.find_window(now)? {
+            None => t.no_meeting_zones.to_string(),
+            Some(MeetingWindow::Overlap { start, end }) => {
+                let ranges = self
+                    .shown_zones()
+                    .map(|tz| {
+                        format_time_range_with_timezone(
+                            &start.with_timezone(tz),
+                            &end.with_timezone(tz),
+                            &self.format,
+                            t,
+                        )
+                    })
+                    .join(" - ");
+                format!("{}\n{ranges}", t.meeting_overlap)
+            }
+            Some(MeetingWindow::LeastBad { start, outside }) => {
+                let times = self
+                    .shown_zones()
+                    .map(|tz| format_time_with_timezone(&start.with_timezone(tz), &self.format, t))
+                    .join(" - ");
+                let outside = outside
+                    .iter()
+                    .map(|tz| format_timezone(&start.with_timezone(tz)))
+                    .join(", ");
+                format!(
+                    "{}\n{times}\n{}",
+                    t.meeting_least_bad,
+                    fill(t.outside_working_hours, &[("zones", &outside)])
+                )
+            }
+        };
+        Ok(ret)
+    }
+}
+
+/// Parses "[date] [working_hours] timezones...", e.g. "tomorrow 10-16 CET BRT".
+impl TryFrom<&str> for MeetingPlanner {
+    type Error = BotError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let split_values: Vec<&str> = input.split_whitespace().collect();
+        let mut rest = split_values.as_slice();
+
+        let date = take_prefix(&mut rest, parse_date_prefix);
+        let hours_token = rest.first().copied().unwrap_or_default();
+        let working_hours = take_prefix(&mut rest, parse_time_range_prefix);
+        // A range like "9-9" or "9-" leaves no working hours at all.
+        let degenerate = match working_hours {
+            Some((start, end)) => start == end,
+            None => hours_token
+                .split_once('-')
+                .is_some_and(|(start, _)| start.starts_with(|c: char| c.is_ascii_digit())),
+        };
+        if degenerate {
+            return Err(BotError::InvalidTime(hours_token.to_string()));
+        }
+        let mut planner = Self::new(timezone_parser(rest)?);
+        planner.date = date;
+        if let Some(working_hours) = working_hours {
+            planner.working_hours = working_hours;
+        }
+        Ok(planner)
+    }
+}
+
+fn hour(hour: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::PST8PDT;
+
+    use super::*;
+    use crate::i18n::ENGLISH;
+
+    fn winter_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap()
+    }
+
+    fn summer_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 7, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_meeting_overlap() {
+        let planner = MeetingPlanner::try_from("CET BRT").unwrap();
+        assert_eq!(
+            planner.describe(winter_now(), &ENGLISH).unwrap(),
+            "Everyone is within working hours:\n12:00-17:00 UTC - 13:00-18:00 CET - 09:00-14:00 BRT"
+        );
+    }
+
+    #[test]
+    fn test_meeting_overlap_follows_dst() {
+        let planner = MeetingPlanner::try_from("CET BRT").unwrap();
+        assert_eq!(
+            planner.describe(summer_now(), &ENGLISH).unwrap(),
+            "Everyone is within working hours:\n12:00-16:00 UTC - 14:00-18:00 CET - 09:00-13:00 BRT"
+        );
+    }
+
+    #[test]
+    fn test_meeting_custom_hours_and_date() {
+        let planner = MeetingPlanner::try_from("tomorrow 10-16 CET BRT").unwrap();
+        assert_eq!(planner.date, Some(DateSpec::Tomorrow));
+        assert_eq!(
+            planner.find_window(winter_now()).unwrap(),
+            Some(MeetingWindow::Overlap {
+                start: Utc.with_ymd_and_hms(2026, 1, 16, 13, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2026, 1, 16, 15, 0, 0).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_meeting_utc_listed_once() {
+        let planner = MeetingPlanner::try_from("UTC CET").unwrap();
+        assert_eq!(
+            planner.describe(winter_now(), &ENGLISH).unwrap(),
+            "Everyone is within working hours:\n09:00-17:00 UTC - 10:00-18:00 CET"
+        );
+    }
+
+    #[test]
+    fn test_meeting_rejects_empty_hours() {
+        for input in ["9-9 CET BRT", "9- CET BRT"] {
+            let hours = input.split(' ').next().unwrap();
+            assert_eq!(
+                MeetingPlanner::try_from(input).err(),
+                Some(BotError::InvalidTime(hours.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_meeting_least_bad() {
+        let planner = MeetingPlanner::try_from("CET BRT PST").unwrap();
+        assert_eq!(
+            planner.find_window(winter_now()).unwrap(),
+            Some(MeetingWindow::LeastBad {
+                start: Utc.with_ymd_and_hms(2026, 1, 15, 16, 0, 0).unwrap(),
+                outside: vec![Zone::from(PST8PDT)],
+            })
+        );
+        assert_eq!(
+            planner.describe(winter_now(), &ENGLISH).unwrap(),
+            "Working hours do not overlap. Least bad option:\n16:00 UTC - 17:00 CET - 13:00 BRT - 08:00 PST\nOutside working hours: PST"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use chrono::NaiveTime;
+
+use crate::converter::Converter;
+use crate::time::{
+    DateSpec, keyword_time, parse_date_prefix, parse_time_prefix, parse_zone_prefix, split_meridiem,
+};
+use crate::zone::Zone;
+
+/// Characters trimmed from words before parsing, e.g. the comma in "3pm,".
+const PUNCTUATION: &[char] = &[',', '.', ';', '!', '?', '(', ')', '"', '\''];
+
+/// A time and zone mentioned somewhere in a free-form message, e.g. "3pm CET
+/// tomorrow" in "let's sync at 3pm CET tomorrow".
+#[derive(Debug, PartialEq)]
+pub struct Mention {
+    pub time: NaiveTime,
+    pub date: Option<DateSpec>,
+    pub zone: Zone,
+}
+
+impl Mention {
+    /// A converter from the mentioned zone into `targets`.
+    pub fn converter(&self, targets: &[Zone]) -> Converter {
+        let mut timezones = vec![self.zone.clone()];
+        timezones.extend(targets.iter().filter(|tz| **tz != self.zone).cloned());
+        Converter::new(Some(self.time), timezones).with_date(self.date)
+    }
+}
+
+/// Finds every time immediately followed by a timezone in `text`. The time
+/// must look like one ("15:00", "3pm") rather than a bare number, and a date
+/// right before the time or right after the zone is picked up too.
+/// Ambiguous or unknown zones are skipped.
+pub fn find_mentions(text: &str) -> Vec<Mention> {
+    let words = words(text);
+    let mut mentions = Vec::new();
+    let mut idx = 0;
+    while idx < words.len() {
+        let Some((mention, len)) = mention_at(&words, idx) else {
+            idx += 1;
+            continue;
+        };
+        mentions.push(mention);
+        idx += len;
+    }
+    mentions
+}
+
+/// Whether `text` mentions a time anywhere, with or without a zone.
+pub fn mentions_time(text: &str) -> bool {
+    let words = words(text);
+    (0..words.len()).any(|idx| {
+        parse_time_prefix(&words[idx..])
+            .is_some_and(|(_, len)| looks_like_time(&words[idx..idx + len]))
+    })
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(PUNCTUATION))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Parses a mention starting at `words[idx]`, returning it and the number of
+/// words it spans from there.
+fn mention_at(words: &[&str], idx: usize) -> Option<(Mention, usize)> {
+    let (time, time_len) = parse_time_prefix(&words[idx..])?;
+    if !looks_like_time(&words[idx..idx + time_len]) {
+        return None;
+    }
+    let zone_words = &words[idx + time_len..];
+    if zone_words
+        .first()
+        .is_some_and(|word| word.len() <= 2 && *word != word.to_uppercase())
+    {
+        // Two letter country codes clash with words like "in" or "at".
+        return None;
+    }
+    let (zone, zone_len) = parse_zone_prefix(zone_words).ok()?;
+    let mut len = time_len + zone_len;
+
+    let date_before = [2, 1].into_iter().find_map(|back| {
+        let (date, date_len) = parse_date_prefix(&words[idx.checked_sub(back)?..idx])?;
+        (date_len == back).then_some(date)
+    });
+    let date_after = parse_date_prefix(&words[idx + len..]);
+    if date_before.is_none()
+        && let Some((_, date_len)) = date_after
+    {
+        len += date_len;
+    }
+    let mention = Mention {
+        time,
+        date: date_before.or(date_after.map(|(date, _)| date)),
+        zone,
+    };
+    Some((mention, len))
+}
+
+fn looks_like_time(words: &[&str]) -> bool {
+    let text = words.join(" ").to_lowercase();
+    text.contains(':') || split_meridiem(&text).is_some() || keyword_time(&text).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::America::Sao_Paulo;
+    use chrono_tz::CET;
+
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_find_mentions() {
+        assert_eq!(
+            find_mentions("let's sync at 3pm CET tomorrow, or 10:30 BRT on friday?"),
+            vec![
+                Mention {
+                    time: time(15, 0),
+                    date: Some(DateSpec::Tomorrow),
+                    zone: CET.into(),
+                },
+                Mention {
+                    time: time(10, 30),
+                    date: None,
+                    zone: Sao_Paulo.into(),
+                },
+            ]
+        );
+        assert_eq!(
+            find_mentions("tomorrow 9:00 new york works")[0],
+            Mention {
+                time: time(9, 0),
+                date: Some(DateSpec::Tomorrow),
+                zone: chrono_tz::America::New_York.into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_mentions_weekday() {
+        assert_eq!(
+            find_mentions("how about next friday 10am PST?")[0].date,
+            Some(DateSpec::NextWeekday(chrono::Weekday::Fri))
+        );
+        assert_eq!(
+            find_mentions("10am PST next friday")[0].date,
+            Some(DateSpec::NextWeekday(chrono::Weekday::Fri))
+        );
+    }
+
+    #[test]
+    fn test_find_mentions_keywords() {
+        assert_eq!(
+            find_mentions("shall we say noon CET?"),
+            vec![Mention {
+                time: time(12, 0),
+                date: None,
+                zone: CET.into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_mentions_ignores_plain_numbers_and_words() {
+        assert_eq!(find_mentions("I have 3 CET tickets"), vec![]);
+        assert_eq!(find_mentions("see you at 3pm in the office"), vec![]);
+        assert_eq!(find_mentions("at 3pm IST"), vec![]);
+        assert_eq!(find_mentions("no times here"), vec![]);
+    }
+
+    #[test]
+    fn test_mentions_time() {
+        assert!(mentions_time("15:00"));
+        assert!(mentions_time("how about 3pm?"));
+        assert!(mentions_time("noon"));
+        assert!(!mentions_time("I have 3 CET tickets"));
+        assert!(!mentions_time("UTC"));
+        assert!(!mentions_time("no"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BotError;
+use crate::time::TimeFormat;
+
+/// Timezone preferences of a Telegram user. Zones are stored as names that
+/// `time::parse_zone` understands.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub home: Option<String>,
+    #[serde(default)]
+    pub favourites: Vec<String>,
+    /// Language code chosen with /lang, overriding the Telegram locale.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Chosen with /format.
+    #[serde(default)]
+    pub format: Option<TimeFormat>,
+}
+
+impl UserSettings {
+    /// The home zone followed by the favourites.
+    pub fn zones(&self) -> impl Iterator<Item = &str> {
+        self.home.iter().chain(&self.favourites).map(String::as_str)
+    }
+}
+
+/// Settings shared by everyone in a Telegram chat, such as the roster of
+/// timezones a group converts bare times into.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatSettings {
+    #[serde(default)]
+    pub zones: Vec<String>,
+    #[serde(default)]
+    pub members: Vec<ChatMember>,
+    /// Whether to look for times in every message, not only ones that are
+    /// entirely a conversion.
+    #[serde(default)]
+    pub scan: bool,
+    /// Chosen with /format chat, taking precedence over members' formats.
+    #[serde(default)]
+    pub format: Option<TimeFormat>,
+}
+
+/// A user who registered their home timezone in a chat. Their zone lives in
+/// their `UserSettings`; only the name shown in the chat is kept here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMember {
+    pub user_id: i64,
+    pub name: String,
+}
+
+pub trait Storage: Send + Sync {
+    fn user_settings(&self, user_id: i64) -> UserSettings;
+    /// Changes a user's settings in place, with no other change to them
+    /// slipping in between reading and writing.
+    fn update_user_settings(
+        &self,
+        user_id: i64,
+        change: Box<dyn FnOnce(&mut UserSettings) + '_>,
+    ) -> Result<(), BotError>;
+    fn chat_settings(&self, chat_id: i64) -> ChatSettings;
+    /// Changes a chat's settings in place, with no other change to them
+    /// slipping in between reading and writing.
+    fn update_chat_settings(
+        &self,
+        chat_id: i64,
+        change: Box<dyn FnOnce(&mut ChatSettings) + '_>,
+    ) -> Result<(), BotError>;
+    /// The id of the bot's reply to a message, so edits update that reply.
+    fn reply_id(&self, chat_id: i64, message_id: i64) -> Option<i64>;
+    fn set_reply_id(&self, chat_id: i64, message_id: i64, reply_id: i64) -> Result<(), BotError>;
+}
+
+/// How many replies per chat are remembered. Only recent messages tend to
+/// be edited.
+const REPLIES_PER_CHAT: usize = 100;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StorageData {
+    #[serde(default)]
+    users: HashMap<i64, UserSettings>,
+    #[serde(default)]
+    chats: HashMap<i64, ChatSettings>,
+    /// Bot reply ids by chat and then by the id of the message replied to.
+    /// Kept in memory only, as they change with every reply and losing them
+    /// merely turns a later edit into a new reply.
+    #[serde(skip)]
+    replies: HashMap<i64, BTreeMap<i64, i64>>,
+}
+
+/// Keeps everything in memory and, when opened with a path, mirrors it to a
+/// JSON file on every change so no external service is needed.
+#[derive(Debug, Default)]
+pub struct JsonStorage {
+    path: Option<PathBuf>,
+    data: Mutex<StorageData>,
+}
+
+impl JsonStorage {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let data = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => StorageData::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path: Some(path),
+            data: Mutex::new(data),
+        })
+    }
+
+    /// Applies `change` and saves the result, keeping the previous data
+    /// when saving fails so memory never holds what the file does not.
+    fn update(&self, change: impl FnOnce(&mut StorageData)) -> Result<(), BotError> {
+        let mut data = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        let mut changed = data.clone();
+        change(&mut changed);
+        if let Some(path) = &self.path {
+            let write = || -> io::Result<()> {
+                // Write to a sibling file first so a crash never leaves a
+                // truncated store behind.
+                let tmp_path = path.with_extension("tmp");
+                fs::write(&tmp_path, serde_json::to_vec_pretty(&changed)?)?;
+                fs::rename(tmp_path, path)
+            };
+            write().map_err(|e| BotError::Storage(e.to_string()))?;
+        }
+        *data = changed;
+        Ok(())
+    }
+
+    fn read<T>(&self, read: impl FnOnce(&StorageData) -> T) -> T {
+        read(&self.data.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Storage for JsonStorage {
+    fn user_settings(&self, user_id: i64) -> UserSettings {
+        self.read(|data| data.users.get(&user_id).cloned().unwrap_or_default())
+    }
+
+    fn update_user_settings(
+        &self,
+        user_id: i64,
+        change: Box<dyn FnOnce(&mut UserSettings) + '_>,
+    ) -> Result<(), BotError> {
+        self.update(|data| change(data.users.entry(user_id).or_default()))
+    }
+
+    fn chat_settings(&self, chat_id: i64) -> ChatSettings {
+        self.read(|data| data.chats.get(&chat_id).cloned().unwrap_or_default())
+    }
+
+    fn update_chat_settings(
+        &self,
+        chat_id: i64,
+        change: Box<dyn FnOnce(&mut ChatSettings) + '_>,
+    ) -> Result<(), BotError> {
+        self.update(|data| change(data.chats.entry(chat_id).or_default()))
+    }
+
+    fn reply_id(&self, chat_id: i64, message_id: i64) -> Option<i64> {
+        self.read(|data| data.replies.get(&chat_id)?.get(&message_id).copied())
+    }
+
+    fn set_reply_id(&self, chat_id: i64, message_id: i64, reply_id: i64) -> Result<(), BotError> {
+        let mut data = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        let replies = data.replies.entry(chat_id).or_default();
+        replies.insert(message_id, reply_id);
+        // Message ids grow over time, so the first ones are the oldest.
+        while replies.len() > REPLIES_PER_CHAT {
+            replies.pop_first();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(home: &str, favourites: &[&str]) -> UserSettings {
+        UserSettings {
+            home: Some(home.into()),
+            favourites: favourites.iter().map(|zone| zone.to_string()).collect(),
+            language: None,
+            format: None,
+        }
+    }
+
+    #[test]
+    fn test_user_settings_default() {
+        let storage = JsonStorage::in_memory();
+        assert_eq!(storage.user_settings(1), UserSettings::default());
+    }
+
+    #[test]
+    fn test_user_settings_zones() {
+        let settings = settings("CET", &["BRT", "UTC"]);
+        assert_eq!(settings.zones().collect::<Vec<_>>(), ["CET", "BRT", "UTC"]);
+    }
+
+    #[test]
+    fn test_reply_ids_keep_most_recent() {
+        let storage = JsonStorage::in_memory();
+        assert_eq!(storage.reply_id(-7, 1), None);
+        for message_id in 1..=REPLIES_PER_CHAT as i64 + 1 {
+            storage
+                .set_reply_id(-7, message_id, message_id + 1000)
+                .unwrap();
+        }
+        assert_eq!(storage.reply_id(-7, 1), None);
+        assert_eq!(storage.reply_id(-7, 2), Some(1002));
+        assert_eq!(storage.reply_id(-8, 2), None);
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_kept() {
+        let storage = JsonStorage::in_memory();
+        std::thread::scope(|scope| {
+            for zone in ["CET", "BRT", "UTC", "JST"] {
+                let storage = &storage;
+                scope.spawn(move || {
+                    let add = |settings: &mut ChatSettings| settings.zones.push(zone.into());
+                    storage.update_chat_settings(-7, Box::new(add)).unwrap();
+                });
+            }
+        });
+        let mut zones = storage.chat_settings(-7).zones;
+        zones.sort();
+        assert_eq!(zones, ["BRT", "CET", "JST", "UTC"]);
+    }
+
+    #[test]
+    fn test_json_storage_persists_to_disk() {
+        let path = std::env::temp_dir().join(format!("chronosbot-{}.json", std::process::id()));
+        let storage = JsonStorage::open(&path).unwrap();
+        let set_user = |user: &mut UserSettings| *user = settings("CET", &["BRT"]);
+        storage
+            .update_user_settings(42, Box::new(set_user))
+            .unwrap();
+
+        let chat = ChatSettings {
+            zones: vec!["UTC".into()],
+            members: vec![ChatMember {
+                user_id: 42,
+                name: "Ana".into(),
+            }],
+            scan: true,
+            format: TimeFormat::parse("12h offset"),
+        };
+        let set_chat = |settings: &mut ChatSettings| *settings = chat.clone();
+        storage
+            .update_chat_settings(-7, Box::new(set_chat))
+            .unwrap();
+
+        storage.set_reply_id(-7, 1, 2).unwrap();
+
+        let reopened = JsonStorage::open(&path).unwrap();
+        assert_eq!(reopened.user_settings(42), settings("CET", &["BRT"]));
+        assert_eq!(reopened.chat_settings(-7), chat);
+        assert_eq!(reopened.reply_id(-7, 1), None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write_keeps_previous_data() {
+        let path = std::env::temp_dir().join("chronosbot-missing-dir/store.json");
+        let storage = JsonStorage::open(path).unwrap();
+        let add = |settings: &mut ChatSettings| settings.zones.push("CET".into());
+        assert!(matches!(
+            storage.update_chat_settings(-7, Box::new(add)),
+            Err(BotError::Storage(_))
+        ));
+        assert_eq!(storage.chat_settings(-7), ChatSettings::default());
+    }
+}
@@ -0,0 +1,128 @@
+use chrono_tz::{TZ_VARIANTS, Tz};
+
+use crate::gazetteer;
+
+/// How many suggestions to offer for an unknown timezone.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Finds the timezone names closest to `input`, comparing against IANA names
+/// (and their city part, e.g. "Amsterdam" for "Europe/Amsterdam") as well as
+/// the places known to the gazetteer. Names leading to the same zone are
+/// suggested once.
+pub fn timezone_suggestions(input: &str) -> Vec<String> {
+    let input = gazetteer::normalize(input);
+    let max_distance = (input.chars().count() / 3).max(1);
+
+    let iana = TZ_VARIANTS.iter().map(|tz| {
+        let name = tz.name();
+        let city = name.rsplit('/').next().unwrap_or(name);
+        let distance = edit_distance(&input, &gazetteer::normalize(name))
+            .min(edit_distance(&input, &gazetteer::normalize(city)));
+        (distance, name.to_string(), *tz)
+    });
+    // Two-letter codes are within reach of any short typo, so they would
+    // crowd out the names that were actually meant.
+    let places = gazetteer::places()
+        .filter(|(place, _)| place.len() > 2)
+        .map(|(place, tz)| (edit_distance(&input, place), place.to_string(), tz));
+
+    let mut candidates: Vec<(usize, String, Tz)> = iana
+        .chain(places)
+        .filter(|(distance, ..)| *distance <= max_distance)
+        .collect();
+    candidates.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    let mut zones = Vec::new();
+    candidates
+        .into_iter()
+        .filter(|(.., tz)| {
+            let new = !zones.contains(tz);
+            zones.push(*tz);
+            new
+        })
+        .map(|(_, name, _)| name)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_zone;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("amsterdam", "amsterdam"), 0);
+        assert_eq!(edit_distance("amsterdm", "amsterdam"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_timezone_suggestions_iana() {
+        let suggestions = timezone_suggestions("Europe/Amsterdm");
+        assert_eq!(
+            suggestions.first().map(String::as_str),
+            Some("Europe/Amsterdam")
+        );
+    }
+
+    #[test]
+    fn test_timezone_suggestions_city_part() {
+        let suggestions = timezone_suggestions("Bucharst");
+        assert!(suggestions.contains(&"Europe/Bucharest".to_string()));
+    }
+
+    #[test]
+    fn test_timezone_suggestions_place() {
+        let suggestions = timezone_suggestions("brazl");
+        assert!(suggestions.contains(&"brazil".to_string()));
+    }
+
+    #[test]
+    fn test_timezone_suggestions_one_per_zone() {
+        assert_eq!(timezone_suggestions("tokio"), ["Asia/Tokyo"]);
+    }
+
+    #[test]
+    fn test_timezone_suggestions_skip_codes() {
+        assert!(timezone_suggestions("ze").is_empty());
+        assert!(!timezone_suggestions("bz").contains(&"br".to_string()));
+    }
+
+    #[test]
+    fn test_timezone_suggestions_parse() {
+        for input in [
+            "ze",
+            "br",
+            "tokio",
+            "brazl",
+            "Bucharst",
+            "nw york",
+            "Europe/Amsterdm",
+        ] {
+            for suggestion in timezone_suggestions(input) {
+                assert!(parse_zone(&suggestion).is_ok(), "{input}: {suggestion}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_timezone_suggestions_none() {
+        assert!(timezone_suggestions("qwertyuiop").is_empty());
+    }
+}
@@ -1,9 +1,11 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 pub enum RequestType {
     Message(Message),
     EditedMessage(Message),
     InlineQuery(InlineQuery),
+    CallbackQuery(CallbackQuery),
     Unknown,
 }
 
@@ -18,6 +20,9 @@ impl RequestType {
         if let Some(inline_query) = request.inline_query {
             return Self::InlineQuery(inline_query);
         }
+        if let Some(callback_query) = request.callback_query {
+            return Self::CallbackQuery(callback_query);
+        }
         Self::Unknown
     }
 }
@@ -28,6 +33,7 @@ pub struct TelegramRequest {
     pub message: Option<Message>,
     pub edited_message: Option<Message>,
     pub inline_query: Option<InlineQuery>,
+    pub callback_query: Option<CallbackQuery>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -46,6 +52,36 @@ impl Message {
     pub fn is_from_bot(&self) -> bool {
         self.from.is_bot || self.via_bot.as_ref().map(|b| b.is_bot).unwrap_or(false)
     }
+
+    /// The command starting the text, as marked by a bot_command entity.
+    /// In groups it may name the bot it is for, e.g. "/now@chronos_bot utc".
+    pub fn bot_command(&self) -> Option<BotCommand<'_>> {
+        let text = self.text.as_deref()?;
+        let entity = self
+            .entities
+            .iter()
+            .flatten()
+            .find(|entity| entity.type_ == "bot_command" && entity.offset == 0)?;
+        // Commands are ASCII, so the UTF-16 length is also the byte length.
+        let command = text.get(..entity.length as usize)?;
+        let (name, bot) = match command.split_once('@') {
+            Some((name, bot)) => (name, Some(bot)),
+            None => (command, None),
+        };
+        Some(BotCommand {
+            name,
+            bot,
+            args: text[command.len()..].trim(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BotCommand<'a> {
+    pub name: &'a str,
+    /// The username after "@", if the command is addressed to a bot.
+    pub bot: Option<&'a str>,
+    pub args: &'a str,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -58,6 +94,16 @@ pub struct User {
     pub language_code: Option<String>,
 }
 
+impl User {
+    /// The name to show other chat members, e.g. "Ana Souza".
+    pub fn display_name(&self) -> String {
+        match &self.last_name {
+            Some(last_name) => format!("{} {last_name}", self.first_name),
+            None => self.first_name.clone(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Chat {
     pub id: i64,
@@ -87,17 +133,37 @@ pub struct InlineQuery {
     pub chat_type: Option<String>,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CallbackQuery {
+    pub id: String,
+    pub from: User,
+    pub message: Option<CallbackMessage>,
+    pub data: Option<String>,
+}
+
+/// The message a callback button was attached to. Only the fields shared
+/// with inaccessible (old) messages are read.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CallbackMessage {
+    pub message_id: i64,
+    pub chat: Chat,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "method", rename_all = "camelCase")]
 pub enum TelegramResponse {
     SendMessage {
         chat_id: i64,
         text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply_markup: Option<InlineKeyboardMarkup>,
     },
     EditMessageText {
         chat_id: i64,
         message_id: i64,
         text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply_markup: Option<InlineKeyboardMarkup>,
     },
     AnswerInlineQuery {
         inline_query_id: String,
@@ -105,6 +171,70 @@ pub enum TelegramResponse {
     },
 }
 
+const BOT_API_URL: &str = "https://api.telegram.org";
+
+impl TelegramResponse {
+    fn method(&self) -> &'static str {
+        match self {
+            Self::SendMessage { .. } => "sendMessage",
+            Self::EditMessageText { .. } => "editMessageText",
+            Self::AnswerInlineQuery { .. } => "answerInlineQuery",
+        }
+    }
+}
+
+/// Calls the Bot API directly, for what answering the webhook cannot do,
+/// such as learning the id of a message sent.
+#[derive(Debug, Clone)]
+pub struct BotApi {
+    url: String,
+    token: String,
+}
+
+impl BotApi {
+    pub fn new(token: String) -> Self {
+        Self::with_url(BOT_API_URL.into(), token)
+    }
+
+    pub fn with_url(url: String, token: String) -> Self {
+        Self { url, token }
+    }
+
+    /// Errors mean Telegram did not take the call. `Ok(None)` means it did,
+    /// but its result could not be read.
+    pub fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+    ) -> Result<Option<T>, Box<ureq::Error>> {
+        let url = format!("{}/bot{}/{method}", self.url, self.token);
+        let response = ureq::post(&url).send_json(params)?;
+        let result = response.into_json::<ApiResponse<T>>().ok();
+        Ok(result.and_then(|result| result.result))
+    }
+
+    /// Sends or edits a message, returning its id.
+    pub fn send(&self, response: &TelegramResponse) -> Result<Option<i64>, Box<ureq::Error>> {
+        let message = self.call::<SentMessage>(response.method(), response)?;
+        Ok(message.map(|message| message.message_id))
+    }
+
+    /// The bot's own account.
+    pub fn get_me(&self) -> Result<Option<User>, Box<ureq::Error>> {
+        self.call("getMe", &serde_json::json!({}))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiResponse<T> {
+    result: Option<T>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SentMessage {
+    message_id: i64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InlineQueryResult {
     #[serde(rename = "type")]
@@ -132,6 +262,33 @@ pub struct InputMessageContent {
     pub message_text: String,
 }
 
+/// Telegram rejects callback data longer than this many bytes.
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InlineKeyboardMarkup {
+    pub inline_keyboard: Vec<Vec<InlineKeyboardButton>>,
+}
+
+impl InlineKeyboardMarkup {
+    /// Builds a keyboard with one button per row, skipping buttons whose
+    /// callback data Telegram would reject. Returns `None` when no button fits.
+    pub fn single_column(buttons: impl IntoIterator<Item = InlineKeyboardButton>) -> Option<Self> {
+        let inline_keyboard: Vec<_> = buttons
+            .into_iter()
+            .filter(|button| button.callback_data.len() <= MAX_CALLBACK_DATA_LEN)
+            .map(|button| vec![button])
+            .collect();
+        (!inline_keyboard.is_empty()).then_some(Self { inline_keyboard })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InlineKeyboardButton {
+    pub text: String,
+    pub callback_data: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +299,7 @@ mod tests {
         let response = TelegramResponse::SendMessage {
             chat_id: 5,
             text: "hi".into(),
+            reply_markup: None,
         };
         assert_eq!(
             serde_json::to_value(&response).unwrap(),
@@ -152,6 +310,7 @@ mod tests {
             chat_id: 5,
             message_id: 7,
             text: "hi".into(),
+            reply_markup: None,
         };
         assert_eq!(
             serde_json::to_value(&response).unwrap(),
@@ -159,6 +318,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inline_keyboard_skips_oversized_callback_data() {
+        let button = |data: &str| InlineKeyboardButton {
+            text: "pick".into(),
+            callback_data: data.into(),
+        };
+        let keyboard =
+            InlineKeyboardMarkup::single_column([button("short"), button(&"x".repeat(65))])
+                .unwrap();
+        assert_eq!(keyboard.inline_keyboard.len(), 1);
+        assert_eq!(keyboard.inline_keyboard[0][0].callback_data, "short");
+
+        assert!(InlineKeyboardMarkup::single_column([button(&"x".repeat(65))]).is_none());
+    }
+
     #[test]
     fn test_request_from_request_unknown() {
         let request = TelegramRequest {
@@ -166,6 +340,7 @@ mod tests {
             message: None,
             edited_message: None,
             inline_query: None,
+            callback_query: None,
         };
 
         assert!(matches!(
@@ -205,6 +380,7 @@ mod tests {
             }),
             edited_message: None,
             inline_query: None,
+            callback_query: None,
         };
 
         assert!(matches!(
@@ -1,37 +1,252 @@
+use std::fmt;
 use std::str::FromStr;
 
-use chrono::{DateTime, LocalResult, NaiveTime, TimeDelta, Utc};
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, LocalResult, NaiveDate, NaiveTime, Offset, TimeDelta,
+    Timelike, Utc, Weekday,
+};
 use chrono_tz::{OffsetComponents, Tz};
+use serde::{Deserialize, Serialize};
 
 use crate::error::BotError;
+use crate::gazetteer;
+use crate::i18n::Messages;
+use crate::suggest;
+use crate::zone::{Zone, ZoneOffset, parse_fixed_offset};
 
-pub fn parse_tz(text: &str) -> Result<Tz, BotError> {
-    let tz = match text.to_lowercase().as_str() {
+/// Abbreviations shared by several timezones, with every zone they may mean.
+const AMBIGUOUS_ABBREVIATIONS: [(&str, &[Tz]); 4] = [
+    (
+        "ist",
+        &[Tz::Europe__Dublin, Tz::Asia__Kolkata, Tz::Asia__Jerusalem],
+    ),
+    (
+        "cst",
+        &[Tz::CST6CDT, Tz::Asia__Shanghai, Tz::America__Havana],
+    ),
+    ("bst", &[Tz::Europe__London, Tz::Asia__Dhaka]),
+    ("ast", &[Tz::America__Halifax, Tz::Asia__Riyadh]),
+];
+
+/// Resolves `text` to every timezone it may refer to. Most names have a
+/// single candidate; abbreviations such as "IST" have several.
+pub fn tz_candidates(text: &str) -> Result<Vec<Tz>, BotError> {
+    let lower_text = text.to_lowercase();
+    if let Some((_, candidates)) = AMBIGUOUS_ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == lower_text)
+    {
+        return Ok(candidates.to_vec());
+    }
+    let tz = match lower_text.as_str() {
         "edt" | "est" => Tz::EST5EDT,
-        "cdt" | "cst" => Tz::CST6CDT,
+        "cdt" => Tz::CST6CDT,
         "mdt" | "mst" => Tz::MST7MDT,
         "pdt" | "pst" => Tz::PST8PDT,
-        "ist" | "irlanda" | "ireland" => Tz::Europe__Dublin,
         "europe" | "eu" => Tz::CET,
-        "madrid" | "barcelona" | "spain" | "es" => Tz::Europe__Madrid,
-        "brazil" | "brasil" | "brt" | "br" => Tz::America__Sao_Paulo,
-        "netherlands" | "amsterdam" | "nl" => Tz::Europe__Amsterdam,
-        "romania" | "romenia" | "ro" => Tz::Europe__Bucharest,
-        _ => Tz::from_str_insensitive(text)?,
+        "brt" => Tz::America__Sao_Paulo,
+        _ => match gazetteer::lookup(text) {
+            Some(tz) => tz,
+            None => Tz::from_str_insensitive(text).map_err(|_| BotError::InvalidTimezone {
+                input: text.to_string(),
+                suggestions: suggest::timezone_suggestions(text),
+            })?,
+        },
     };
-    Ok(tz)
+    Ok(vec![tz])
+}
+
+pub fn parse_tz(text: &str) -> Result<Tz, BotError> {
+    match tz_candidates(text)?.as_slice() {
+        [tz] => Ok(*tz),
+        candidates => Err(BotError::AmbiguousTimezone {
+            input: text.to_string(),
+            candidates: candidates.to_vec(),
+        }),
+    }
+}
+
+/// Parses a timezone, accepting fixed UTC offsets as well as the names
+/// understood by `parse_tz`.
+pub fn parse_zone(text: &str) -> Result<Zone, BotError> {
+    match parse_fixed_offset(text) {
+        Some(zone) => Ok(zone),
+        None => parse_tz(text).map(Zone::from),
+    }
+}
+
+/// Parses a timezone from the start of `tokens`, preferring the longest
+/// multi-word place name (e.g. "new york"). Returns the zone and the number
+/// of tokens used.
+pub fn parse_zone_prefix(tokens: &[&str]) -> Result<(Zone, usize), BotError> {
+    let longest = tokens.len().min(gazetteer::MAX_NAME_WORDS);
+    let place = (2..=longest)
+        .rev()
+        .find_map(|len| Some((gazetteer::lookup(&tokens[..len].join(" "))?, len)));
+    match place {
+        Some((tz, len)) => Ok((tz.into(), len)),
+        None => Ok((parse_zone(tokens.first().copied().unwrap_or_default())?, 1)),
+    }
+}
+
+/// How times are written in replies, chosen per user or per chat with
+/// /format. The default is a plain 24-hour clock, e.g. "09:00 BRT".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeFormat {
+    #[serde(default)]
+    pub twelve_hour: bool,
+    #[serde(default)]
+    pub seconds: bool,
+    /// Adds the UTC offset, e.g. "09:00 BRT (UTC-3)".
+    #[serde(default)]
+    pub offset: bool,
+    #[serde(default)]
+    pub weekday: bool,
+    #[serde(default)]
+    pub date: bool,
+}
+
+impl TimeFormat {
+    /// Parses options such as "12h seconds offset". Options not given are
+    /// off, and "24h" is accepted for clarity.
+    pub fn parse(options: &str) -> Option<Self> {
+        let mut format = Self::default();
+        for option in options.split_whitespace() {
+            match option.to_lowercase().as_str() {
+                "12h" => format.twelve_hour = true,
+                "24h" => format.twelve_hour = false,
+                "seconds" => format.seconds = true,
+                "offset" => format.offset = true,
+                "weekday" => format.weekday = true,
+                "date" => format.date = true,
+                _ => return None,
+            }
+        }
+        Some(format)
+    }
+
+    fn clock_pattern(&self) -> &'static str {
+        match (self.twelve_hour, self.seconds) {
+            (false, false) => "%H:%M",
+            (false, true) => "%H:%M:%S",
+            (true, false) => "%-I:%M %p",
+            (true, true) => "%-I:%M:%S %p",
+        }
+    }
+
+    /// The weekday and date written before a time, e.g. "Thu 2026-01-15 ".
+    fn day_prefix(&self, time: &DateTime<Zone>, t: &Messages) -> String {
+        let mut prefix = String::new();
+        if self.weekday {
+            prefix += t.weekdays[time.weekday().num_days_from_monday() as usize];
+            prefix += " ";
+        }
+        if self.date {
+            prefix += &time.format("%Y-%m-%d ").to_string();
+        }
+        prefix
+    }
+}
+
+impl fmt::Display for TimeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.twelve_hour { "12h" } else { "24h" })?;
+        let options = [
+            (self.seconds, "seconds"),
+            (self.offset, "offset"),
+            (self.weekday, "weekday"),
+            (self.date, "date"),
+        ];
+        for (_, name) in options.iter().filter(|(enabled, _)| *enabled) {
+            write!(f, " {name}")?;
+        }
+        Ok(())
+    }
 }
 
-pub fn format_time(time: &DateTime<Tz>) -> String {
-    time.format("%H:%M").to_string()
+pub fn format_time(time: &DateTime<Zone>, format: &TimeFormat) -> String {
+    time.format(format.clock_pattern()).to_string()
 }
 
-pub fn format_time_with_timezone(time: &DateTime<Tz>) -> String {
-    format!("{} {}", format_time(time), format_timezone(time))
+pub fn format_time_with_timezone(
+    time: &DateTime<Zone>,
+    format: &TimeFormat,
+    t: &Messages,
+) -> String {
+    format!(
+        "{}{} {}{}",
+        format.day_prefix(time, t),
+        format_time(time, format),
+        format_timezone(time),
+        offset_suffix(time, format)
+    )
 }
 
-pub fn format_timezone(time: &DateTime<Tz>) -> String {
-    match time.timezone() {
+/// " (UTC-3)" when the format asks for offsets. Fixed offsets already read
+/// as one, so they get nothing.
+fn offset_suffix(time: &DateTime<Zone>, format: &TimeFormat) -> String {
+    if !format.offset || matches!(time.timezone(), Zone::Fixed { .. }) {
+        return String::new();
+    }
+    let seconds = time.offset().fix().local_minus_utc();
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    let sign = if seconds < 0 { '-' } else { '+' };
+    match (hours, minutes) {
+        (0, 0) => " (UTC)".to_string(),
+        (hours, 0) => format!(" (UTC{sign}{hours})"),
+        (hours, minutes) => format!(" (UTC{sign}{hours}:{minutes:02})"),
+    }
+}
+
+/// Marks a time that falls on a different calendar day than the source time,
+/// e.g. " (+1)" for the next day.
+pub fn format_day_shift(days: i64) -> String {
+    match days {
+        0 => String::new(),
+        days => format!(" ({days:+})"),
+    }
+}
+
+/// Formats both ends of a range in their timezone, flagging ranges whose end
+/// falls on a later day than their start.
+pub fn format_time_range_with_timezone(
+    start: &DateTime<Zone>,
+    end: &DateTime<Zone>,
+    format: &TimeFormat,
+    t: &Messages,
+) -> String {
+    let range = format!(
+        "{}{}-{} {}{}",
+        format.day_prefix(start, t),
+        format_time(start, format),
+        format_time(end, format),
+        format_timezone(start),
+        offset_suffix(start, format)
+    );
+    let crosses_midnight = end.date_naive() > start.date_naive() && end.time() != NaiveTime::MIN;
+    if crosses_midnight {
+        format!("{range} {}", t.crosses_midnight)
+    } else {
+        range
+    }
+}
+
+/// Working hours, as local hours of the day, assumed for everyone.
+pub const WORKING_HOURS: (u32, u32) = (9, 18);
+
+/// Whether `time` falls within working hours on a weekday.
+pub fn is_working_hours(time: &DateTime<Zone>) -> bool {
+    let (start, end) = WORKING_HOURS;
+    let weekend = matches!(time.weekday(), Weekday::Sat | Weekday::Sun);
+    !weekend && (start..end).contains(&time.hour())
+}
+
+pub fn format_timezone(time: &DateTime<Zone>) -> String {
+    let tz = match time.timezone() {
+        Zone::Named(tz) => tz,
+        Zone::Fixed { label, .. } => return label,
+    };
+    match tz {
         Tz::America__Sao_Paulo => "BRT".to_string(),
         Tz::EST5EDT => dst_aware_abbreviation(time, "EST", "EDT"),
         Tz::CST6CDT => dst_aware_abbreviation(time, "CST", "CDT"),
@@ -42,11 +257,69 @@ pub fn format_timezone(time: &DateTime<Tz>) -> String {
     }
 }
 
-fn is_dst(time: &DateTime<Tz>) -> bool {
-    time.offset().dst_offset() != TimeDelta::zero()
+pub fn is_dst(time: &DateTime<Zone>) -> bool {
+    match time.offset() {
+        ZoneOffset::Named(offset) => offset.dst_offset() != TimeDelta::zero(),
+        ZoneOffset::Fixed { .. } => false,
+    }
+}
+
+/// How far `offset_change` looks for a change of UTC offset.
+const OFFSET_CHANGE_SEARCH: TimeDelta = TimeDelta::days(731);
+
+/// A moment when a zone's UTC offset changes, e.g. a DST transition.
+#[derive(Debug, PartialEq)]
+pub struct OffsetChange {
+    pub at: DateTime<Utc>,
+    pub before: FixedOffset,
+    pub after: FixedOffset,
+}
+
+/// Finds the closest offset change in `tz` after `from`, or before it when
+/// `forward` is false, within the next or last two years.
+pub fn offset_change(tz: &Zone, from: DateTime<Utc>, forward: bool) -> Option<OffsetChange> {
+    let offset_at = |time: DateTime<Utc>| time.with_timezone(tz).offset().fix();
+    // Offsets change on whole seconds, so the search can ignore fractions.
+    let from = from.with_nanosecond(0).unwrap_or(from);
+    // Near the ends of chrono's range the search stops where the range does.
+    let (step, limit) = if forward {
+        (
+            TimeDelta::days(1),
+            from.checked_add_signed(OFFSET_CHANGE_SEARCH),
+        )
+    } else {
+        (
+            -TimeDelta::days(1),
+            from.checked_sub_signed(OFFSET_CHANGE_SEARCH),
+        )
+    };
+    let initial = offset_at(from);
+    let (mut near, mut far) = (from, from);
+    while offset_at(far) == initial {
+        if Some(far) == limit {
+            return None;
+        }
+        near = far;
+        far = far.checked_add_signed(step)?;
+    }
+    // Narrow down to the second the offset changes.
+    while (far - near).abs() > TimeDelta::seconds(1) {
+        let middle = near + TimeDelta::seconds((far - near).num_seconds() / 2);
+        if offset_at(middle) == initial {
+            near = middle;
+        } else {
+            far = middle;
+        }
+    }
+    let (before, after) = if forward { (near, far) } else { (far, near) };
+    Some(OffsetChange {
+        at: after,
+        before: offset_at(before),
+        after: offset_at(after),
+    })
 }
 
-fn dst_aware_abbreviation(time: &DateTime<Tz>, standard: &str, daylight: &str) -> String {
+fn dst_aware_abbreviation(time: &DateTime<Zone>, standard: &str, daylight: &str) -> String {
     if is_dst(time) { daylight } else { standard }.to_string()
 }
 
@@ -59,29 +332,333 @@ fn clean_time(time: &str) -> String {
     }
 }
 
-pub fn parse_time(text: &str) -> Result<NaiveTime, BotError> {
+const MERIDIEM_SUFFIXES: [(&str, bool); 6] = [
+    ("a.m.", false),
+    ("p.m.", true),
+    ("am", false),
+    ("pm", true),
+    ("a", false),
+    ("p", true),
+];
+
+/// Splits a trailing AM/PM marker from `text`, returning the clock part and
+/// whether the marker was PM. A lone "a" or "p" only counts attached to the
+/// number, as in "3p", since on its own it is more likely a word.
+pub(crate) fn split_meridiem(text: &str) -> Option<(&str, bool)> {
+    MERIDIEM_SUFFIXES.iter().find_map(|(suffix, is_pm)| {
+        let unspaced = text.strip_suffix(suffix)?;
+        let clock = unspaced.trim_end();
+        if suffix.len() == 1 && clock.len() != unspaced.len() {
+            return None;
+        }
+        clock
+            .starts_with(|c: char| c.is_ascii_digit())
+            .then_some((clock, *is_pm))
+    })
+}
+
+fn parse_24h_time(text: &str) -> Option<NaiveTime> {
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
     let clean_text = clean_time(text);
-    NaiveTime::from_str(&clean_text)
-        .ok()
-        .or_else(|| {
-            let hour = clean_text.parse::<u32>().ok()?;
-            NaiveTime::from_hms_opt(hour, 0, 0)
-        })
-        .ok_or_else(|| BotError::InvalidTime(text.to_string()))
+    NaiveTime::from_str(&clean_text).ok().or_else(|| {
+        let hour = clean_text.parse::<u32>().ok()?;
+        NaiveTime::from_hms_opt(hour, 0, 0)
+    })
+}
+
+fn parse_12h_time(clock: &str, is_pm: bool) -> Option<NaiveTime> {
+    let time = parse_24h_time(clock)?;
+    if !(1..=12).contains(&time.hour()) {
+        return None;
+    }
+    let hour = time.hour() % 12 + if is_pm { 12 } else { 0 };
+    time.with_hour(hour)
+}
+
+/// Words that stand for a time of day, normalized like gazetteer names.
+/// End of day and the morning follow `WORKING_HOURS`.
+const TIME_KEYWORDS: &[(&str, u32)] = &[
+    ("noon", 12),
+    ("midday", 12),
+    ("midnight", 0),
+    ("morning", WORKING_HOURS.0),
+    ("end of day", WORKING_HOURS.1),
+    ("eod", WORKING_HOURS.1),
+    ("cob", WORKING_HOURS.1),
+    ("close of business", WORKING_HOURS.1),
+    // Portuguese
+    ("meio dia", 12),
+    ("meia noite", 0),
+    ("fim do dia", WORKING_HOURS.1),
+    // Spanish
+    ("mediodia", 12),
+    ("medianoche", 0),
+    ("fin del dia", WORKING_HOURS.1),
+];
+
+/// The longest keyword is "close of business".
+const MAX_TIME_WORDS: usize = 3;
+
+pub(crate) fn keyword_time(text: &str) -> Option<NaiveTime> {
+    let normalized = gazetteer::normalize(text);
+    let (_, hour) = TIME_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == normalized)?;
+    NaiveTime::from_hms_opt(*hour, 0, 0)
+}
+
+pub fn parse_time(text: &str) -> Result<NaiveTime, BotError> {
+    if let Some(time) = keyword_time(text) {
+        return Ok(time);
+    }
+    let lower_text = text.trim().to_lowercase();
+    match split_meridiem(&lower_text) {
+        Some((clock, is_pm)) => parse_12h_time(clock, is_pm),
+        None => parse_24h_time(&lower_text),
+    }
+    .ok_or_else(|| BotError::InvalidTime(text.to_string()))
+}
+
+/// Parses a time from the start of `tokens`, allowing it to span several
+/// tokens (e.g. "3:30 PM" or "end of day"). Returns the time and the number
+/// of tokens used.
+pub fn parse_time_prefix(tokens: &[&str]) -> Option<(NaiveTime, usize)> {
+    (1..=tokens.len().min(MAX_TIME_WORDS))
+        .rev()
+        .find_map(|len| Some((parse_time(&tokens[..len].join(" ")).ok()?, len)))
+}
+
+/// Parses a Unix timestamp in seconds (10 digits) or milliseconds (13
+/// digits), as found in logs.
+pub fn parse_epoch(text: &str) -> Option<DateTime<Utc>> {
+    if !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: i64 = text.parse().ok()?;
+    match text.len() {
+        10 => DateTime::from_timestamp(value, 0),
+        13 => DateTime::from_timestamp_millis(value),
+        _ => None,
+    }
+}
+
+/// Parses ISO 8601 / RFC 3339 datetimes with an offset, such as
+/// "2026-10-17T14:00:00Z" or "2026-10-17T14:00+02:00". The offset becomes
+/// the datetime's zone: UTC for "Z", otherwise a fixed offset.
+pub fn parse_iso_datetime(text: &str) -> Option<DateTime<Zone>> {
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+        "%Y-%m-%dT%H:%M:%S%.f%z",
+        "%Y-%m-%dT%H:%M%:z",
+        "%Y-%m-%dT%H:%M%z",
+    ];
+    if let Some(utc_text) = text.strip_suffix(['Z', 'z']) {
+        let datetime = FORMATS.iter().find_map(|format| {
+            DateTime::parse_from_str(&format!("{utc_text}+00:00"), format).ok()
+        })?;
+        return Some(datetime.with_timezone(&Zone::from(Tz::UTC)));
+    }
+    let datetime = FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(text, format).ok())?;
+    let label = &text[text.rfind(['+', '-'])?..];
+    Some(datetime.with_timezone(&Zone::fixed(*datetime.offset(), label)))
+}
+
+fn duration_unit(unit: &str) -> Option<TimeDelta> {
+    match unit {
+        "d" | "day" | "days" => Some(TimeDelta::days(1)),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(TimeDelta::hours(1)),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(TimeDelta::minutes(1)),
+        _ => None,
+    }
+}
+
+/// Parses compact durations such as "2h", "90m" or "1h30m".
+fn parse_compact_duration(text: &str) -> Option<TimeDelta> {
+    let mut total = TimeDelta::zero();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit_end = rest[digits_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |idx| digits_end + idx);
+        let amount: i32 = rest[..digits_end].parse().ok()?;
+        let delta = duration_unit(&rest[digits_end..unit_end])?.checked_mul(amount)?;
+        total = total.checked_add(&delta)?;
+        rest = &rest[unit_end..];
+    }
+    (total != TimeDelta::zero()).then_some(total)
+}
+
+/// Parses a time range such as "9-17", "9h-18h" or "9:00-17:30" from the
+/// first token. Returns the start, the end and the number of tokens used.
+pub fn parse_time_range_prefix(tokens: &[&str]) -> Option<((NaiveTime, NaiveTime), usize)> {
+    let (start, end) = tokens.first()?.split_once('-')?;
+    Some(((parse_time(start).ok()?, parse_time(end).ok()?), 1))
+}
+
+/// Parses a relative offset from `now` at the start of `tokens`, written as
+/// "in 2h", "+90m" or "in 1 day 3 hours". Returns the offset and the number
+/// of tokens used.
+pub fn parse_relative_prefix(tokens: &[&str]) -> Option<(TimeDelta, usize)> {
+    let lower_tokens: Vec<String> = tokens.iter().map(|t| t.to_lowercase()).collect();
+    let mut total = match lower_tokens.first()?.as_str() {
+        "in" | "+" => TimeDelta::zero(),
+        token => parse_compact_duration(token.strip_prefix('+')?)?,
+    };
+
+    let mut idx = 1;
+    while let Some(token) = lower_tokens.get(idx) {
+        if let Some(delta) = parse_compact_duration(token) {
+            total = total.checked_add(&delta)?;
+            idx += 1;
+            continue;
+        }
+        let unit = lower_tokens
+            .get(idx + 1)
+            .and_then(|unit| duration_unit(unit));
+        match (token.parse::<i32>(), unit) {
+            (Ok(amount), Some(unit)) => {
+                total = total.checked_add(&unit.checked_mul(amount)?)?;
+                idx += 2;
+            }
+            _ => break,
+        }
+    }
+    (total != TimeDelta::zero()).then_some((total, idx))
+}
+
+/// A calendar date as written by the user, resolved relative to "today" in
+/// the source timezone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateSpec {
+    Date(NaiveDate),
+    DayMonth {
+        day: u32,
+        month: u32,
+    },
+    Today,
+    Tomorrow,
+    Yesterday,
+    /// Today or the upcoming day with this weekday.
+    Weekday(Weekday),
+    /// "next friday": the coming day with this weekday, a week ahead when
+    /// that is today.
+    NextWeekday(Weekday),
+}
+
+impl DateSpec {
+    pub fn resolve(&self, today: NaiveDate) -> Result<NaiveDate, BotError> {
+        let date = match *self {
+            Self::Date(date) => Some(date),
+            Self::DayMonth { day, month } => NaiveDate::from_ymd_opt(today.year(), month, day)
+                .filter(|date| *date >= today)
+                .or_else(|| NaiveDate::from_ymd_opt(today.year() + 1, month, day)),
+            Self::Today => Some(today),
+            Self::Tomorrow => today.succ_opt(),
+            Self::Yesterday => today.pred_opt(),
+            Self::Weekday(weekday) => {
+                let days_ahead = weekday.days_since(today.weekday());
+                today.checked_add_days(Days::new(days_ahead.into()))
+            }
+            Self::NextWeekday(weekday) => {
+                let days_ahead = match weekday.days_since(today.weekday()) {
+                    0 => 7,
+                    days => days,
+                };
+                today.checked_add_days(Days::new(days_ahead.into()))
+            }
+        };
+        date.ok_or_else(|| BotError::InvalidDate(self.to_string()))
+    }
+
+    /// Whether the date was given as a day of the week, so the weekday is
+    /// worth showing next to converted times.
+    pub fn is_weekday(&self) -> bool {
+        matches!(self, Self::Weekday(_) | Self::NextWeekday(_))
+    }
+}
+
+impl fmt::Display for DateSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Date(date) => write!(f, "{date}"),
+            Self::DayMonth { day, month } => write!(f, "{day:02}/{month:02}"),
+            Self::Today => f.write_str("today"),
+            Self::Tomorrow => f.write_str("tomorrow"),
+            Self::Yesterday => f.write_str("yesterday"),
+            Self::Weekday(weekday) => write!(f, "{weekday}"),
+            Self::NextWeekday(weekday) => write!(f, "next {weekday}"),
+        }
+    }
+}
+
+fn parse_day_month(text: &str) -> Option<DateSpec> {
+    let mut parts = text.split('/');
+    let day = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some(year) => NaiveDate::from_ymd_opt(year.parse().ok()?, month, day).map(DateSpec::Date),
+        // 2000 is a leap year, so this accepts 29/02.
+        None => {
+            NaiveDate::from_ymd_opt(2000, month, day).map(|_| DateSpec::DayMonth { day, month })
+        }
+    }
+}
+
+/// Whether `text` is shaped like a numeric date ("2026-02-30", "30/02",
+/// "30/02/2026"), valid or not, as opposed to a time range like "9-17".
+pub fn looks_like_date(text: &str) -> bool {
+    let numeric = |parts: &[&str]| {
+        parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    };
+    let dashed: Vec<&str> = text.split('-').collect();
+    let slashed: Vec<&str> = text.split('/').collect();
+    (dashed.len() == 3 && numeric(&dashed)) || (matches!(slashed.len(), 2 | 3) && numeric(&slashed))
+}
+
+/// Parses a date from the start of `tokens`, including the two-word "next
+/// <weekday>". Returns the date and the number of tokens used.
+pub fn parse_date_prefix(tokens: &[&str]) -> Option<(DateSpec, usize)> {
+    if let [next, weekday, ..] = tokens
+        && next.eq_ignore_ascii_case("next")
+        && let Ok(weekday) = Weekday::from_str(weekday)
+    {
+        return Some((DateSpec::NextWeekday(weekday), 2));
+    }
+    Some((parse_date(tokens.first()?).ok()?, 1))
+}
+
+pub fn parse_date(text: &str) -> Result<DateSpec, BotError> {
+    let date = match text.to_lowercase().as_str() {
+        "today" => Some(DateSpec::Today),
+        "tomorrow" => Some(DateSpec::Tomorrow),
+        "yesterday" => Some(DateSpec::Yesterday),
+        lower_text => NaiveDate::parse_from_str(lower_text, "%Y-%m-%d")
+            .ok()
+            .map(DateSpec::Date)
+            .or_else(|| parse_day_month(lower_text))
+            .or_else(|| Weekday::from_str(lower_text).ok().map(DateSpec::Weekday)),
+    };
+    date.ok_or_else(|| BotError::InvalidDate(text.to_string()))
 }
 
 pub fn time_with_timezone(
     time: &NaiveTime,
-    tz: &Tz,
-    now: DateTime<Utc>,
-) -> Result<DateTime<Tz>, BotError> {
-    let date = now.with_timezone(tz).date_naive();
-    match date.and_time(*time).and_local_timezone(*tz) {
+    tz: &Zone,
+    date: NaiveDate,
+) -> Result<DateTime<Zone>, BotError> {
+    match date.and_time(*time).and_local_timezone(tz.clone()) {
         LocalResult::Single(datetime) => Ok(datetime),
         LocalResult::Ambiguous(earliest, _) => Ok(earliest),
         LocalResult::None => Err(BotError::NonexistentTime {
             time: *time,
-            tz: *tz,
+            tz: tz.clone(),
         }),
     }
 }
@@ -89,6 +666,7 @@ pub fn time_with_timezone(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::i18n::ENGLISH;
     use chrono::TimeZone;
 
     fn winter_now() -> DateTime<Utc> {
@@ -99,6 +677,24 @@ mod tests {
         Utc.with_ymd_and_hms(2026, 7, 15, 12, 0, 0).unwrap()
     }
 
+    #[test]
+    fn test_parse_time_keywords() {
+        let hour = |hour| NaiveTime::from_hms_opt(hour, 0, 0);
+        assert_eq!(parse_time("noon").ok(), hour(12));
+        assert_eq!(parse_time("Midnight").ok(), hour(0));
+        assert_eq!(parse_time("EOD").ok(), hour(18));
+        assert_eq!(parse_time("meio-dia").ok(), hour(12));
+        assert_eq!(parse_time("mediodía").ok(), hour(12));
+        assert_eq!(
+            parse_time_prefix(&["end", "of", "day", "CET"]),
+            Some((hour(18).unwrap(), 3))
+        );
+        assert_eq!(
+            parse_time_prefix(&["fin", "del", "día"]),
+            Some((hour(18).unwrap(), 3))
+        );
+    }
+
     #[test]
     fn test_parse_time_complete() {
         let result = parse_time("12:13:45");
@@ -129,47 +725,337 @@ mod tests {
         assert_eq!(result.ok(), NaiveTime::from_hms_opt(12, 30, 0));
     }
 
+    #[test]
+    fn test_parse_time_meridiem() {
+        assert_eq!(parse_time("3pm").ok(), NaiveTime::from_hms_opt(15, 0, 0));
+        assert_eq!(
+            parse_time("3:30 PM").ok(),
+            NaiveTime::from_hms_opt(15, 30, 0)
+        );
+        assert_eq!(parse_time("11a").ok(), NaiveTime::from_hms_opt(11, 0, 0));
+        assert_eq!(parse_time("9 p.m.").ok(), NaiveTime::from_hms_opt(21, 0, 0));
+        assert_eq!(parse_time("12am").ok(), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(parse_time("12pm").ok(), NaiveTime::from_hms_opt(12, 0, 0));
+        assert_eq!(
+            parse_time("12:30am").ok(),
+            NaiveTime::from_hms_opt(0, 30, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_time_meridiem_invalid() {
+        assert_eq!(
+            parse_time("13pm"),
+            Err(BotError::InvalidTime("13pm".into()))
+        );
+        assert_eq!(parse_time("0am"), Err(BotError::InvalidTime("0am".into())));
+        assert_eq!(parse_time("pm"), Err(BotError::InvalidTime("pm".into())));
+        assert_eq!(parse_time("3 p"), Err(BotError::InvalidTime("3 p".into())));
+        assert_eq!(parse_time("3 a"), Err(BotError::InvalidTime("3 a".into())));
+    }
+
+    #[test]
+    fn test_parse_time_prefix() {
+        assert_eq!(
+            parse_time_prefix(&["3:30", "PM", "BRT"]),
+            Some((NaiveTime::from_hms_opt(15, 30, 0).unwrap(), 2))
+        );
+        assert_eq!(
+            parse_time_prefix(&["12", "BRT"]),
+            Some((NaiveTime::from_hms_opt(12, 0, 0).unwrap(), 1))
+        );
+        assert_eq!(parse_time_prefix(&["BRT"]), None);
+    }
+
+    #[test]
+    fn test_parse_time_rejects_offsets() {
+        assert_eq!(parse_time("+02"), Err(BotError::InvalidTime("+02".into())));
+    }
+
     #[test]
     fn test_parse_time_invalid() {
         let result = parse_time("HALO");
         assert_eq!(result, Err(BotError::InvalidTime("HALO".into())));
     }
 
+    #[test]
+    fn test_parse_time_range_prefix() {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(
+            parse_time_range_prefix(&["9-17", "CET"]),
+            Some(((time(9), time(17)), 1))
+        );
+        assert_eq!(
+            parse_time_range_prefix(&["9am-5pm"]),
+            Some(((time(9), time(17)), 1))
+        );
+        assert_eq!(parse_time_range_prefix(&["9-"]), None);
+        assert_eq!(parse_time_range_prefix(&["12:00"]), None);
+    }
+
+    #[test]
+    fn test_parse_relative_prefix() {
+        assert_eq!(
+            parse_relative_prefix(&["in", "2h", "BRT"]),
+            Some((TimeDelta::hours(2), 2))
+        );
+        assert_eq!(
+            parse_relative_prefix(&["+90m"]),
+            Some((TimeDelta::minutes(90), 1))
+        );
+        assert_eq!(
+            parse_relative_prefix(&["in", "45", "minutes", "BRT", "CET"]),
+            Some((TimeDelta::minutes(45), 3))
+        );
+        assert_eq!(
+            parse_relative_prefix(&["in", "1", "day", "3", "hours"]),
+            Some((TimeDelta::hours(27), 5))
+        );
+        assert_eq!(
+            parse_relative_prefix(&["in", "1h30m"]),
+            Some((TimeDelta::minutes(90), 2))
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_prefix_invalid() {
+        assert_eq!(parse_relative_prefix(&["2h", "BRT"]), None);
+        assert_eq!(parse_relative_prefix(&["in", "BRT"]), None);
+        assert_eq!(parse_relative_prefix(&["in", "2", "BRT"]), None);
+        assert_eq!(parse_relative_prefix(&["+2"]), None);
+        let huge = ["2147483647d"; 64];
+        assert_eq!(parse_relative_prefix(&[&["in"], &huge[..]].concat()), None);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("2026-11-03"),
+            Ok(DateSpec::Date(
+                NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()
+            ))
+        );
+        assert_eq!(
+            parse_date("03/11"),
+            Ok(DateSpec::DayMonth { day: 3, month: 11 })
+        );
+        assert_eq!(
+            parse_date("03/11/2027"),
+            Ok(DateSpec::Date(
+                NaiveDate::from_ymd_opt(2027, 11, 3).unwrap()
+            ))
+        );
+        assert_eq!(parse_date("Tomorrow"), Ok(DateSpec::Tomorrow));
+        assert_eq!(parse_date("fri"), Ok(DateSpec::Weekday(Weekday::Fri)));
+        assert_eq!(
+            parse_date("31/02"),
+            Err(BotError::InvalidDate("31/02".into()))
+        );
+        assert_eq!(
+            parse_date("12:00"),
+            Err(BotError::InvalidDate("12:00".into()))
+        );
+    }
+
+    #[test]
+    fn test_looks_like_date() {
+        for text in ["2026-02-30", "30/02", "31/12/2026"] {
+            assert!(looks_like_date(text), "{text}");
+        }
+        for text in ["9-17", "12:00", "friday", "2026-02", "/02"] {
+            assert!(!looks_like_date(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_date_prefix() {
+        assert_eq!(
+            parse_date_prefix(&["next", "Friday", "10:00"]),
+            Some((DateSpec::NextWeekday(Weekday::Fri), 2))
+        );
+        assert_eq!(
+            parse_date_prefix(&["mon", "10:00"]),
+            Some((DateSpec::Weekday(Weekday::Mon), 1))
+        );
+        assert_eq!(parse_date_prefix(&["next", "week"]), None);
+    }
+
+    #[test]
+    fn test_date_spec_resolve() {
+        // 2026-01-15 is a Thursday.
+        let today = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let resolve = |spec: DateSpec| spec.resolve(today).unwrap();
+        assert_eq!(resolve(DateSpec::Tomorrow), today.succ_opt().unwrap());
+        assert_eq!(
+            resolve(DateSpec::Weekday(Weekday::Mon)),
+            NaiveDate::from_ymd_opt(2026, 1, 19).unwrap()
+        );
+        assert_eq!(resolve(DateSpec::Weekday(Weekday::Thu)), today);
+        assert_eq!(
+            resolve(DateSpec::NextWeekday(Weekday::Thu)),
+            NaiveDate::from_ymd_opt(2026, 1, 22).unwrap()
+        );
+        assert_eq!(
+            resolve(DateSpec::NextWeekday(Weekday::Fri)),
+            NaiveDate::from_ymd_opt(2026, 1, 16).unwrap()
+        );
+        assert_eq!(
+            resolve(DateSpec::DayMonth { day: 3, month: 11 }),
+            NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()
+        );
+        assert_eq!(
+            resolve(DateSpec::DayMonth { day: 1, month: 1 }),
+            NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()
+        );
+        assert_eq!(
+            DateSpec::DayMonth { day: 29, month: 2 }.resolve(today),
+            Err(BotError::InvalidDate("29/02".into()))
+        );
+    }
+
     #[test]
     fn test_parse_tz() {
         assert_eq!(parse_tz("UTC"), Ok(Tz::UTC));
         assert_eq!(parse_tz("BRT"), Ok(Tz::America__Sao_Paulo));
         assert_eq!(parse_tz("CET"), Ok(Tz::CET));
         assert_eq!(parse_tz("PST"), Ok(Tz::PST8PDT));
+        assert_eq!(parse_tz("tokyo"), Ok(Tz::Asia__Tokyo));
+        assert_eq!(parse_tz("DE"), Ok(Tz::Europe__Berlin));
+        assert_eq!(parse_tz("romenia"), Ok(Tz::Europe__Bucharest));
+    }
+
+    #[test]
+    fn test_parse_tz_ambiguous() {
+        assert_eq!(
+            tz_candidates("IST"),
+            Ok(vec![
+                Tz::Europe__Dublin,
+                Tz::Asia__Kolkata,
+                Tz::Asia__Jerusalem
+            ])
+        );
+        assert_eq!(
+            parse_tz("cst"),
+            Err(BotError::AmbiguousTimezone {
+                input: "cst".into(),
+                candidates: vec![Tz::CST6CDT, Tz::Asia__Shanghai, Tz::America__Havana],
+            })
+        );
+        assert_eq!(parse_tz("cdt"), Ok(Tz::CST6CDT));
+    }
+
+    #[test]
+    fn test_parse_zone_prefix_multi_word() {
+        assert_eq!(
+            parse_zone_prefix(&["new", "york", "CET"]),
+            Ok((Tz::America__New_York.into(), 2))
+        );
+        assert_eq!(
+            parse_zone_prefix(&["rio", "de", "janeiro"]),
+            Ok((Tz::America__Sao_Paulo.into(), 3))
+        );
+        assert_eq!(parse_zone_prefix(&["CET", "BRT"]), Ok((Tz::CET.into(), 1)));
+    }
+
+    #[test]
+    fn test_format_day_shift() {
+        assert_eq!(format_day_shift(0), "");
+        assert_eq!(format_day_shift(1), " (+1)");
+        assert_eq!(format_day_shift(-1), " (-1)");
+    }
+
+    #[test]
+    fn test_time_format_parse() {
+        let format = TimeFormat::parse("12h Seconds offset").unwrap();
+        assert!(format.twelve_hour && format.seconds && format.offset);
+        assert!(!format.weekday && !format.date);
+        assert_eq!(format.to_string(), "12h seconds offset");
+        assert_eq!(TimeFormat::parse("").unwrap(), TimeFormat::default());
+        assert_eq!(TimeFormat::default().to_string(), "24h");
+        assert_eq!(TimeFormat::parse("13h"), None);
+    }
+
+    #[test]
+    fn test_format_time_with_timezone_profiles() {
+        let zone = Zone::from(Tz::America__Sao_Paulo);
+        let time = winter_now().with_timezone(&zone);
+        let format = |options| {
+            format_time_with_timezone(&time, &TimeFormat::parse(options).unwrap(), &ENGLISH)
+        };
+        assert_eq!(format(""), "09:00 BRT");
+        assert_eq!(format("12h"), "9:00 AM BRT");
+        assert_eq!(format("seconds offset"), "09:00:00 BRT (UTC-3)");
+        assert_eq!(format("weekday date"), "Thu 2026-01-15 09:00 BRT");
+
+        let india = winter_now().with_timezone(&Zone::from(Tz::Asia__Kolkata));
+        let offset = TimeFormat::parse("offset").unwrap();
+        assert_eq!(
+            format_time_with_timezone(&india, &offset, &ENGLISH),
+            "17:30 Asia/Kolkata (UTC+5:30)"
+        );
+        let utc = winter_now().with_timezone(&Zone::from(Tz::UTC));
+        assert_eq!(
+            format_time_with_timezone(&utc, &offset, &ENGLISH),
+            "12:00 UTC (UTC)"
+        );
+        let weekday = TimeFormat::parse("weekday").unwrap();
+        assert_eq!(
+            format_time_with_timezone(&time, &weekday, &crate::i18n::SPANISH),
+            "jue 09:00 BRT"
+        );
     }
 
     #[test]
     fn test_format_timezone() {
         let now = winter_now();
-        assert_eq!(format_timezone(&now.with_timezone(&Tz::UTC)), "UTC");
         assert_eq!(
-            format_timezone(&now.with_timezone(&Tz::America__Sao_Paulo)),
+            format_timezone(&now.with_timezone(&Zone::Named(Tz::UTC))),
+            "UTC"
+        );
+        assert_eq!(
+            format_timezone(&now.with_timezone(&Zone::Named(Tz::America__Sao_Paulo))),
             "BRT"
         );
-        assert_eq!(format_timezone(&now.with_timezone(&Tz::CET)), "CET");
+        assert_eq!(
+            format_timezone(&now.with_timezone(&Zone::Named(Tz::CET))),
+            "CET"
+        );
+    }
+
+    #[test]
+    fn test_parse_zone() {
+        assert_eq!(parse_zone("CET"), Ok(Zone::Named(Tz::CET)));
+        assert_eq!(
+            parse_zone("UTC+5:30"),
+            Ok(Zone::fixed(
+                FixedOffset::east_opt(19800).unwrap(),
+                "UTC+5:30"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_format_timezone_fixed_offset() {
+        let zone = parse_zone("GMT-3").unwrap();
+        assert_eq!(format_timezone(&winter_now().with_timezone(&zone)), "GMT-3");
     }
 
     #[test]
     fn test_format_timezone_dst_aware() {
         assert_eq!(
-            format_timezone(&winter_now().with_timezone(&Tz::PST8PDT)),
+            format_timezone(&winter_now().with_timezone(&Zone::Named(Tz::PST8PDT))),
             "PST"
         );
         assert_eq!(
-            format_timezone(&summer_now().with_timezone(&Tz::PST8PDT)),
+            format_timezone(&summer_now().with_timezone(&Zone::Named(Tz::PST8PDT))),
             "PDT"
         );
         assert_eq!(
-            format_timezone(&winter_now().with_timezone(&Tz::EST5EDT)),
+            format_timezone(&winter_now().with_timezone(&Zone::Named(Tz::EST5EDT))),
             "EST"
         );
         assert_eq!(
-            format_timezone(&summer_now().with_timezone(&Tz::EST5EDT)),
+            format_timezone(&summer_now().with_timezone(&Zone::Named(Tz::EST5EDT))),
             "EDT"
         );
     }
@@ -177,23 +1063,97 @@ mod tests {
     #[test]
     fn test_time_with_timezone_nonexistent() {
         // CET skips 02:00-03:00 on 2026-03-29 (spring forward).
-        let now = Utc.with_ymd_and_hms(2026, 3, 29, 12, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
         let time = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
         assert_eq!(
-            time_with_timezone(&time, &Tz::CET, now),
-            Err(BotError::NonexistentTime { time, tz: Tz::CET })
+            time_with_timezone(&time, &Tz::CET.into(), date),
+            Err(BotError::NonexistentTime {
+                time,
+                tz: Tz::CET.into()
+            })
         );
     }
 
     #[test]
     fn test_time_with_timezone_ambiguous_picks_earliest() {
         // CET repeats 02:00-03:00 on 2026-10-25 (fall back).
-        let now = Utc.with_ymd_and_hms(2026, 10, 25, 12, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap();
         let time = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
-        let result = time_with_timezone(&time, &Tz::CET, now).unwrap();
+        let result = time_with_timezone(&time, &Tz::CET.into(), date).unwrap();
         assert_eq!(
             result.to_utc(),
             Utc.with_ymd_and_hms(2026, 10, 25, 0, 30, 0).unwrap()
         );
     }
+
+    #[test]
+    fn test_is_working_hours() {
+        let cet = Zone::from(chrono_tz::CET);
+        let at = |d, h| {
+            Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0)
+                .unwrap()
+                .with_timezone(&cet)
+        };
+        assert!(is_working_hours(&at(15, 8)));
+        assert!(!is_working_hours(&at(15, 17)));
+        assert!(!is_working_hours(&at(15, 7)));
+        // Saturday
+        assert!(!is_working_hours(&at(17, 10)));
+    }
+
+    #[test]
+    fn test_offset_change() {
+        let cet = Zone::from(chrono_tz::CET);
+        let now = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+        let hours = |hours| FixedOffset::east_opt(hours * 3600).unwrap();
+        assert_eq!(
+            offset_change(&cet, now, true),
+            Some(OffsetChange {
+                at: Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap(),
+                before: hours(1),
+                after: hours(2),
+            })
+        );
+        assert_eq!(
+            offset_change(&cet, now, false),
+            Some(OffsetChange {
+                at: Utc.with_ymd_and_hms(2025, 10, 26, 1, 0, 0).unwrap(),
+                before: hours(2),
+                after: hours(1),
+            })
+        );
+        assert_eq!(offset_change(&Zone::from(Tz::UTC), now, true), None);
+    }
+
+    #[test]
+    fn test_parse_epoch() {
+        let expected = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+        assert_eq!(parse_epoch("1768478400"), Some(expected));
+        assert_eq!(
+            parse_epoch("1768478400250"),
+            Some(expected + TimeDelta::milliseconds(250))
+        );
+        assert_eq!(parse_epoch("12"), None);
+        assert_eq!(parse_epoch("176847840"), None);
+        assert_eq!(parse_epoch("-768478400"), None);
+    }
+
+    #[test]
+    fn test_parse_iso_datetime() {
+        let expected = Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+        let utc = parse_iso_datetime("2026-10-17T12:00:00Z").unwrap();
+        assert_eq!(utc, expected);
+        assert_eq!(utc.timezone(), Tz::UTC);
+
+        let fixed = parse_iso_datetime("2026-10-17T14:00+02:00").unwrap();
+        assert_eq!(fixed, expected);
+        assert_eq!(format_timezone(&fixed), "+02:00");
+        assert_eq!(
+            parse_iso_datetime("2026-10-17T09:00:00.250-0300").unwrap(),
+            expected + TimeDelta::milliseconds(250)
+        );
+
+        assert_eq!(parse_iso_datetime("2026-10-17T14:00"), None);
+        assert_eq!(parse_iso_datetime("2026-10-17"), None);
+    }
 }
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use chrono::{FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::{Tz, TzOffset};
+
+/// A timezone the bot can convert between: either an IANA zone or a fixed
+/// UTC offset labelled the way the user wrote it (e.g. "UTC+5:30").
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Fixed { offset: FixedOffset, label: String },
+}
+
+#[derive(Debug, Clone)]
+pub enum ZoneOffset {
+    Named(TzOffset),
+    Fixed { offset: FixedOffset, label: String },
+}
+
+impl Zone {
+    pub fn fixed(offset: FixedOffset, label: impl Into<String>) -> Self {
+        Self::Fixed {
+            offset,
+            label: label.into(),
+        }
+    }
+}
+
+impl From<Tz> for Zone {
+    fn from(tz: Tz) -> Self {
+        Self::Named(tz)
+    }
+}
+
+impl PartialEq<Tz> for Zone {
+    fn eq(&self, other: &Tz) -> bool {
+        matches!(self, Self::Named(tz) if tz == other)
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(tz) => write!(f, "{tz}"),
+            Self::Fixed { label, .. } => f.write_str(label),
+        }
+    }
+}
+
+impl ZoneOffset {
+    fn fixed(offset: FixedOffset, label: &str) -> Self {
+        Self::Fixed {
+            offset,
+            label: label.to_string(),
+        }
+    }
+}
+
+impl Offset for ZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        match self {
+            Self::Named(offset) => offset.fix(),
+            Self::Fixed { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ZoneOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(offset) => write!(f, "{offset}"),
+            Self::Fixed { offset, .. } => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl TimeZone for Zone {
+    type Offset = ZoneOffset;
+
+    fn from_offset(offset: &ZoneOffset) -> Self {
+        match offset {
+            ZoneOffset::Named(offset) => Self::Named(Tz::from_offset(offset)),
+            ZoneOffset::Fixed { offset, label } => Self::fixed(*offset, label.clone()),
+        }
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Self::Named(tz) => tz.offset_from_local_date(local).map(ZoneOffset::Named),
+            Self::Fixed { offset, label } => {
+                MappedLocalTime::Single(ZoneOffset::fixed(*offset, label))
+            }
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Self::Named(tz) => tz.offset_from_local_datetime(local).map(ZoneOffset::Named),
+            Self::Fixed { offset, label } => {
+                MappedLocalTime::Single(ZoneOffset::fixed(*offset, label))
+            }
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+        match self {
+            Self::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_date(utc)),
+            Self::Fixed { offset, label } => ZoneOffset::fixed(*offset, label),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+        match self {
+            Self::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_datetime(utc)),
+            Self::Fixed { offset, label } => ZoneOffset::fixed(*offset, label),
+        }
+    }
+}
+
+/// Parses fixed UTC offsets such as "UTC+5:30", "GMT-3", "+0200" or "-03:00".
+pub fn parse_fixed_offset(text: &str) -> Option<Zone> {
+    let lower_text = text.to_lowercase();
+    let signed = ["utc", "gmt"]
+        .iter()
+        .find_map(|prefix| lower_text.strip_prefix(prefix))
+        .unwrap_or(&lower_text);
+    let (sign, digits) = match signed.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if digits.len() > 2 => digits.split_at_checked(digits.len() - 2)?,
+        None => (digits, "00"),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !all_digits(hours) || hours.len() > 2 || !all_digits(minutes) || minutes.len() != 2 {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    let offset = FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))?;
+    Some(Zone::fixed(offset, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn offset(hours: i32, minutes: i32) -> FixedOffset {
+        FixedOffset::east_opt(hours * 3600 + hours.signum() * minutes * 60).unwrap()
+    }
+
+    #[test]
+    fn test_parse_fixed_offset() {
+        assert_eq!(
+            parse_fixed_offset("UTC+5:30"),
+            Some(Zone::fixed(offset(5, 30), "UTC+5:30"))
+        );
+        assert_eq!(
+            parse_fixed_offset("GMT-3"),
+            Some(Zone::fixed(offset(-3, 0), "GMT-3"))
+        );
+        assert_eq!(
+            parse_fixed_offset("+0200"),
+            Some(Zone::fixed(offset(2, 0), "+0200"))
+        );
+        assert_eq!(
+            parse_fixed_offset("utc+05:45"),
+            Some(Zone::fixed(offset(5, 45), "utc+05:45"))
+        );
+        assert_eq!(
+            parse_fixed_offset("-03:00"),
+            Some(Zone::fixed(offset(-3, 0), "-03:00"))
+        );
+    }
+
+    #[test]
+    fn test_parse_fixed_offset_invalid() {
+        assert_eq!(parse_fixed_offset("UTC"), None);
+        assert_eq!(parse_fixed_offset("UTC+15"), None);
+        assert_eq!(parse_fixed_offset("+05:60"), None);
+        assert_eq!(parse_fixed_offset("+5:3"), None);
+        assert_eq!(parse_fixed_offset("+2h"), None);
+        assert_eq!(parse_fixed_offset("12:00"), None);
+        assert_eq!(parse_fixed_offset("+é1"), None);
+        assert_eq!(parse_fixed_offset("UTC+ü5"), None);
+        assert_eq!(parse_fixed_offset("+1é"), None);
+    }
+
+    #[test]
+    fn test_fixed_zone_conversion() {
+        let zone = parse_fixed_offset("UTC+5:30").unwrap();
+        let time = Utc
+            .with_ymd_and_hms(2026, 1, 15, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&zone);
+        assert_eq!(time.timezone(), zone);
+        assert_eq!(time.offset().fix(), offset(5, 30));
+        assert_eq!(time.format("%H:%M").to_string(), "17:30");
+    }
+}